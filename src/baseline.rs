@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lint::Diagnostic;
+
+/// Identifies a finding independently of its line number, so that it is still recognized after
+/// unrelated code was added above it
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Fingerprint {
+    check: String,
+    file: String,
+
+    /// Source line of the finding with its whitespace normalized
+    snippet: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Baseline {
+    findings: Vec<Fingerprint>,
+}
+
+/// Reads the fingerprints of the baseline, returns `None` if there is no baseline file
+pub fn read(path: &str) -> Result<Option<Vec<Fingerprint>>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str::<Baseline>(&content)
+            .map(|baseline| Some(baseline.findings))
+            .map_err(|error| format!("Can't parse {path} : {error}")),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("Can't read {path} : {error}")),
+    }
+}
+
+/// Records the diagnostics as the findings to ignore in the next lint runs
+pub fn write(path: &str, diagnostics: &[Diagnostic]) -> Result<(), String> {
+    let mut findings = fingerprints(diagnostics);
+    findings.sort();
+
+    let content = serde_json::to_string_pretty(&Baseline { findings })
+        .map_err(|error| format!("Can't serialize baseline : {error}"))?;
+
+    std::fs::write(path, content + "\n").map_err(|error| format!("Can't write {path} : {error}"))
+}
+
+/// Keeps the diagnostics that are not in the baseline, returns them with the number of
+/// diagnostics that were suppressed. A finding recorded once only suppresses one diagnostic
+pub fn remove_known(
+    diagnostics: Vec<Diagnostic>,
+    baseline: Vec<Fingerprint>,
+) -> (Vec<Diagnostic>, usize) {
+    let mut known = HashMap::<Fingerprint, usize>::new();
+
+    for fingerprint in baseline {
+        *known.entry(fingerprint).or_default() += 1;
+    }
+
+    let fingerprints = fingerprints(&diagnostics);
+
+    let mut new_diagnostics = Vec::new();
+    let mut suppressed_count = 0;
+
+    for (diagnostic, fingerprint) in diagnostics.into_iter().zip(fingerprints) {
+        match known.get_mut(&fingerprint) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                suppressed_count += 1;
+            }
+            _ => new_diagnostics.push(diagnostic),
+        }
+    }
+
+    (new_diagnostics, suppressed_count)
+}
+
+fn fingerprints(diagnostics: &[Diagnostic]) -> Vec<Fingerprint> {
+    let mut sources = HashMap::<&str, Vec<String>>::new();
+
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let lines = sources.entry(&diagnostic.file).or_insert_with(|| {
+                std::fs::read_to_string(&diagnostic.file)
+                    .map(|content| content.lines().map(str::to_owned).collect())
+                    .unwrap_or_default()
+            });
+
+            let snippet = usize::try_from(diagnostic.line)
+                .ok()
+                .and_then(|line| lines.get(line.checked_sub(1)?))
+                .map_or_else(String::new, |line| {
+                    line.split_whitespace().collect::<Vec<_>>().join(" ")
+                });
+
+            Fingerprint {
+                check: diagnostic.check.clone().unwrap_or_default(),
+                file: diagnostic.file.clone(),
+                snippet,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,172 @@
+use std::{
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+};
+
+use walkdir::WalkDir;
+
+const CLANG_TIDY_FILE: &str = ".clang-tidy";
+
+/// First line of the generated files, files without it were written by the user and are never
+/// overwritten
+const GENERATED_HEADER: &str =
+    "# Generated by embargo from Embargo.toml, edit Embargo.toml instead";
+
+#[derive(Clone, Default)]
+pub struct TidyConfig {
+    /// Check patterns, patterns starting with `-` disable checks
+    pub checks: Vec<String>,
+    pub warnings_as_errors: Vec<String>,
+    pub check_options: Vec<(String, String)>,
+}
+
+/// Settings applied on top of the project settings to the files of a directory
+pub struct TidyOverride {
+    pub path: String,
+    pub config: TidyConfig,
+}
+
+impl TidyConfig {
+    /// Settings that can't be given to the linter on the command line
+    const fn needs_file(&self) -> bool {
+        !self.warnings_as_errors.is_empty() || !self.check_options.is_empty()
+    }
+}
+
+/// Writes the `.clang-tidy` files of the project root and of the overridden directories, so
+/// that `embargo lint` and editors use the same checks. A `.clang-tidy` written by the user is
+/// never replaced : returns false if the checks must be given to the linter instead
+pub fn write_config_files(
+    config: &TidyConfig,
+    overrides: &[TidyOverride],
+    ignored_dirs: &[&str],
+) -> Result<bool, String> {
+    let root_file = Path::new(CLANG_TIDY_FILE);
+
+    let mut generated = Vec::new();
+
+    if is_user_file(root_file) {
+        if config.needs_file() || !overrides.is_empty() {
+            eprintln!(
+                "{CLANG_TIDY_FILE} was not generated by embargo, the linter settings that need \
+                 it are ignored, move its settings to Embargo.toml and remove it to use them"
+            );
+        }
+    } else {
+        write_config_file(root_file, config, false)?;
+        generated.push(root_file.to_owned());
+
+        for tidy_override in overrides {
+            let dir = Path::new(&tidy_override.path);
+
+            if !dir.is_dir() {
+                return Err(format!(
+                    "Linter override path {} is not a directory",
+                    tidy_override.path
+                ));
+            }
+
+            let path = dir.join(CLANG_TIDY_FILE);
+
+            // The file of the user is kept, it applies to the directory instead
+            if !is_user_file(&path) {
+                write_config_file(&path, &tidy_override.config, true)?;
+                generated.push(path);
+            }
+        }
+    }
+
+    remove_stale_files(&generated, ignored_dirs)?;
+
+    Ok(!generated.is_empty())
+}
+
+/// Returns true if the file exists and was not generated by embargo
+fn is_user_file(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| !content.starts_with(GENERATED_HEADER))
+}
+
+fn write_config_file(path: &Path, config: &TidyConfig, inherit: bool) -> Result<(), String> {
+    let mut content = String::new();
+
+    content.push_str(GENERATED_HEADER);
+    content.push('\n');
+
+    if inherit {
+        content.push_str("InheritParentConfig: true\n");
+    }
+
+    // Writing to a String can't fail
+    let _ = writeln!(content, "Checks: {}", quote(&config.checks.join(",")));
+    if !config.warnings_as_errors.is_empty() {
+        let _ = writeln!(
+            content,
+            "WarningsAsErrors: {}",
+            quote(&config.warnings_as_errors.join(","))
+        );
+    }
+
+    if !config.check_options.is_empty() {
+        content.push_str("CheckOptions:\n");
+
+        for (key, value) in &config.check_options {
+            let _ = writeln!(content, "  {}: {}", quote(key), quote(value));
+        }
+    }
+
+    // Rewriting an unchanged file would wake up `embargo watch`
+    if std::fs::read_to_string(path).is_ok_and(|previous| previous == content) {
+        return Ok(());
+    }
+
+    std::fs::write(path, content)
+        .map_err(|error| format!("Can't write {} : {error}", path.display()))
+}
+
+/// Removes the generated files that are no longer needed, like the files of removed overrides.
+/// Hidden directories and `ignored_dirs` are not searched
+fn remove_stale_files(generated: &[PathBuf], ignored_dirs: &[&str]) -> Result<(), String> {
+    let walker = WalkDir::new(".").into_iter().filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+
+        entry.depth() == 0
+            || !entry.file_type().is_dir()
+            || !(name.starts_with('.') || entry.depth() == 1 && ignored_dirs.contains(&&*name))
+    });
+
+    for entry in walker {
+        let entry = entry.map_err(|error| format!("Error can't read entry : {error}"))?;
+
+        if entry.file_name() != CLANG_TIDY_FILE || !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+
+        let is_generated = std::fs::read_to_string(path)
+            .is_ok_and(|content| content.starts_with(GENERATED_HEADER));
+
+        if is_generated
+            && !generated
+                .iter()
+                .any(|file| normalize(file) == normalize(path))
+        {
+            std::fs::remove_file(path)
+                .map_err(|error| format!("Can't remove {} : {error}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Path without `.` components, override paths can be written `./src/third_party`
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+/// Single quoted YAML string
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,71 @@
+use std::{
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+use clap::ValueEnum;
+
+const GREEN: &str = "\x1b[1;32m";
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum ColorChoice {
+    /// Colors when the output is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+/// Sets the `--color` choice, called once before running the command
+pub fn init(choice: ColorChoice) {
+    let _ = CHOICE.set(choice);
+}
+
+/// Returns true if the text written to the stream (by embargo or by the tools it runs) should
+/// be colored
+pub fn enabled(stream: Stream) -> bool {
+    match CHOICE.get().copied().unwrap_or_default() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                false
+            } else if std::env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                true
+            } else {
+                match stream {
+                    Stream::Stdout => io::stdout().is_terminal(),
+                    Stream::Stderr => io::stderr().is_terminal(),
+                }
+            }
+        }
+    }
+}
+
+/// Message telling the command succeeded, printed on stdout
+pub fn success(message: &str) -> String {
+    paint(message, GREEN, Stream::Stdout)
+}
+
+/// Message telling the command failed, printed on stderr
+pub fn error(message: &str) -> String {
+    paint(message, RED, Stream::Stderr)
+}
+
+fn paint(message: &str, color: &str, stream: Stream) -> String {
+    if enabled(stream) {
+        format!("{color}{message}{RESET}")
+    } else {
+        message.to_owned()
+    }
+}
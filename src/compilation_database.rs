@@ -0,0 +1,44 @@
+use serde_json::json;
+
+pub const COMPILATION_DATABASE_FILE: &str = "compile_commands.json";
+
+/// Command compiling a source file of the project to its object
+pub struct CompileCommand {
+    pub compiler: String,
+    pub flags: Vec<String>,
+    pub file: String,
+    pub output: String,
+}
+
+/// Writes the `compile_commands.json` compilation database, used by clangd and clang tools to
+/// know the exact flags of each source file
+pub fn write(commands: &[CompileCommand]) -> Result<(), String> {
+    let root = std::fs::canonicalize(".")
+        .map_err(|error| format!("Can't find the project directory : {error}"))?;
+
+    let entries = commands
+        .iter()
+        .map(|command| {
+            let mut arguments = vec![command.compiler.clone()];
+            arguments.extend(command.flags.iter().cloned());
+            arguments.extend([
+                "-c".to_owned(),
+                command.file.clone(),
+                format!("-o{}", command.output),
+            ]);
+
+            json!({
+                "directory": root,
+                "file": command.file,
+                "output": command.output,
+                "arguments": arguments,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let content = serde_json::to_string_pretty(&entries)
+        .map_err(|error| format!("Can't serialize {COMPILATION_DATABASE_FILE} : {error}"))?;
+
+    std::fs::write(COMPILATION_DATABASE_FILE, content)
+        .map_err(|error| format!("Can't write {COMPILATION_DATABASE_FILE} : {error}"))
+}
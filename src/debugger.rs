@@ -0,0 +1,129 @@
+use std::{path::Path, process::Command};
+
+const CRASH_MESSAGE: &str = "Your app crashed, backtrace :";
+
+const LLDB_QUIT_WITH_EXIT_STATUS: &str =
+    "script lldb.debugger.HandleCommand('quit %d' % lldb.process.GetExitStatus())";
+
+// Selects the innermost frame with debug information, so that the failing line of the app is
+// shown instead of a libc frame when the crash comes from abort()
+const LLDB_SELECT_SOURCE_FRAME: &str =
+    "script frame = next((f for f in lldb.thread if f.GetLineEntry().IsValid()), None); \
+     lldb.debugger.HandleCommand('frame select %d' % (frame.GetFrameID() if frame else 0))";
+
+const LLDB_QUIT_WITH_SIGNAL: &str = "script lldb.debugger.HandleCommand('quit %d' % \
+     (128 + lldb.thread.GetStopReasonDataAtIndex(0) \
+     if lldb.thread.GetStopReason() == lldb.eStopReasonSignal else 1))";
+
+const GDB_QUIT_IF_EXITED: &str = "python code = gdb.convenience_variable('_exitcode'); \
+     gdb.execute('quit %d' % code) if code is not None else None";
+
+// Same as LLDB_SELECT_SOURCE_FRAME
+const GDB_SELECT_SOURCE_FRAME: &str = "python exec('frame = gdb.newest_frame()\\n\
+     while frame is not None and frame.find_sal().symtab is None:\\n    frame = frame.older()\\n\
+     if frame is not None:\\n    frame.select()')";
+
+/// The family of debugger embargo is driving, each one takes different command line arguments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebuggerKind {
+    Lldb,
+    Gdb,
+
+    /// Unknown debugger, the app path is passed as its only argument
+    Generic,
+}
+
+impl DebuggerKind {
+    /// Parses the value of the `debugger-kind` configuration key
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lldb" => Some(Self::Lldb),
+            "gdb" => Some(Self::Gdb),
+            "generic" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+
+    /// Guesses the debugger kind from its executable name (`lldb-17`, `gdb-multiarch`,
+    /// `arm-none-eabi-gdb`, ...)
+    pub fn detect(debugger: &str) -> Self {
+        let name = Path::new(debugger)
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().to_lowercase());
+
+        if name.starts_with("lldb") {
+            Self::Lldb
+        } else if name.starts_with("gdb") || name.ends_with("-gdb") {
+            Self::Gdb
+        } else {
+            Self::Generic
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Lldb => "lldb",
+            Self::Gdb => "gdb",
+            Self::Generic => "generic",
+        }
+    }
+
+    /// Command running the app until it exits, if it crashes a backtrace and the failing source
+    /// line are printed, the debugger then exits with the app status (128 + signal on crash)
+    pub fn run_command(self, debugger: &str, app: &str) -> Command {
+        let mut command = Command::new(debugger);
+
+        match self {
+            Self::Lldb => {
+                command.args(["--batch", "--source-quietly"]);
+                command.args(["-O", "settings set auto-confirm true"]);
+                command.args(["-o", "run"]);
+                command.args(["-o", LLDB_QUIT_WITH_EXIT_STATUS]);
+
+                // Only executed when the app stopped because of a crash
+                command.args(["-k", &format!("script print('\\n{CRASH_MESSAGE}')")]);
+                command.args(["-k", "thread backtrace"]);
+                command.args(["-k", LLDB_SELECT_SOURCE_FRAME]);
+                command.args(["-k", LLDB_QUIT_WITH_SIGNAL]);
+                command.arg(app);
+            }
+            Self::Gdb => {
+                command.args(["-q", "-batch"]);
+                command.args(["-ex", "set pagination off"]);
+                command.args(["-ex", "set confirm off"]);
+                command.args(["-ex", "run"]);
+                command.args(["-ex", GDB_QUIT_IF_EXITED]);
+
+                // Only reached when the app stopped because of a crash
+                command.args(["-ex", &format!("echo \\n{CRASH_MESSAGE}\\n")]);
+                command.args(["-ex", "bt"]);
+                command.args(["-ex", GDB_SELECT_SOURCE_FRAME]);
+                command.args(["-ex", "frame"]);
+                command.args(["-ex", "quit 128 + $_siginfo.si_signo"]);
+                command.arg("--args");
+                command.arg(app);
+            }
+            Self::Generic => {
+                command.arg(app);
+            }
+        }
+
+        command
+    }
+
+    /// Command opening the app inside the debugger for an interactive session
+    pub fn interactive_command(self, debugger: &str, app: &str) -> Command {
+        let mut command = Command::new(debugger);
+
+        match self {
+            Self::Lldb | Self::Generic => {}
+            Self::Gdb => {
+                command.arg("-q");
+            }
+        }
+
+        command.arg(app);
+
+        command
+    }
+}
@@ -0,0 +1,122 @@
+/// Parses a make style dependency file as written by `-MMD` and returns the prerequisites of
+/// its target (the source file followed by every header it includes)
+pub fn parse(content: &str) -> Vec<String> {
+    let mut dependencies = Vec::new();
+
+    let Some(prerequisites) = skip_target(content) else {
+        return dependencies;
+    };
+
+    let mut current = String::new();
+    let mut chars = prerequisites.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                // Line continuation
+                Some('\n' | '\r') => {
+                    push_dependency(&mut dependencies, &mut current);
+                }
+                // Escaped space in a file name
+                Some(' ') => {
+                    current.push(' ');
+                    chars.next();
+                }
+                // Windows path separator
+                _ => current.push('\\'),
+            },
+            '$' if chars.peek() == Some(&'$') => {
+                current.push('$');
+                chars.next();
+            }
+            c if c.is_whitespace() => push_dependency(&mut dependencies, &mut current),
+            c => current.push(c),
+        }
+    }
+
+    push_dependency(&mut dependencies, &mut current);
+
+    dependencies
+}
+
+fn skip_target(content: &str) -> Option<&str> {
+    let bytes = content.as_bytes();
+
+    // The target is separated from its prerequisites by a colon followed by whitespace, other
+    // colons can be found in the target path on Windows (C:\...)
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte == b':' && bytes.get(i + 1).is_none_or(u8::is_ascii_whitespace) {
+            return Some(&content[i + 1..]);
+        }
+    }
+
+    None
+}
+
+fn push_dependency(dependencies: &mut Vec<String>, current: &mut String) {
+    if !current.is_empty() {
+        dependencies.push(std::mem::take(current));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn single_line() {
+        assert_eq!(
+            parse("build/debug/src/main.cpp.o: src/main.cpp include/a.h\n"),
+            ["src/main.cpp", "include/a.h"]
+        );
+    }
+
+    #[test]
+    fn line_continuations() {
+        assert_eq!(
+            parse("main.o: src/main.cpp \\\n  include/a.h \\\n  include/b.h\n"),
+            ["src/main.cpp", "include/a.h", "include/b.h"]
+        );
+    }
+
+    #[test]
+    fn escaped_spaces() {
+        assert_eq!(
+            parse("main.o: src/my\\ file.cpp include/other\\ dir/a.h\n"),
+            ["src/my file.cpp", "include/other dir/a.h"]
+        );
+    }
+
+    #[test]
+    fn escaped_dollar() {
+        assert_eq!(
+            parse("main.o: src/cost$$.cpp include/$$a.h\n"),
+            ["src/cost$.cpp", "include/$a.h"]
+        );
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        assert_eq!(
+            parse("main.o: src/main.cpp \\\r\n  include/a.h\r\n"),
+            ["src/main.cpp", "include/a.h"]
+        );
+    }
+
+    #[test]
+    fn windows_drive_letter_target() {
+        assert_eq!(
+            parse(
+                "C:\\project\\build\\main.o: C:\\project\\src\\main.cpp \\\r\n  \
+                 C:\\project\\include\\a.h\r\n"
+            ),
+            ["C:\\project\\src\\main.cpp", "C:\\project\\include\\a.h"]
+        );
+    }
+
+    #[test]
+    fn empty_or_invalid() {
+        assert!(parse("").is_empty());
+        assert!(parse("no target here").is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    fmt,
+    process::{ExitCode, ExitStatus},
+};
+
+const GENERIC_EXIT_CODE: u8 = 1;
+const CONFIG_EXIT_CODE: u8 = 2;
+const COMPILE_EXIT_CODE: u8 = 3;
+const LINK_EXIT_CODE: u8 = 4;
+const LINT_EXIT_CODE: u8 = 5;
+const FORMAT_EXIT_CODE: u8 = 6;
+
+/// Reasons an embargo command can fail, each one is reported with its own process exit code
+pub enum Error {
+    /// Embargo.toml is missing or invalid, or the command can't be used here
+    Config(String),
+
+    /// Embargo couldn't drive the build (missing compiler, unreadable directory, ...)
+    Build(String),
+
+    /// Any other error embargo ran into while executing a command
+    Io(String),
+
+    /// At least one source file failed to compile
+    Compile,
+
+    /// The linker reported an error
+    Link,
+
+    /// The linter reported an error, or findings missing from the lint baseline
+    Lint,
+
+    /// Some files are not formatted
+    Format(usize),
+
+    /// The app (or the debugger running it) exited with a non-zero status
+    App(u8),
+}
+
+impl Error {
+    /// Returns `Ok` if the process succeeded, or the app exit status otherwise
+    pub fn check_app_status(status: ExitStatus) -> Result<(), Self> {
+        if status.success() {
+            Ok(())
+        } else {
+            Err(Self::App(status_code(status)))
+        }
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Self::Config(_) => CONFIG_EXIT_CODE,
+            Self::Build(_) | Self::Io(_) => GENERIC_EXIT_CODE,
+            Self::Compile => COMPILE_EXIT_CODE,
+            Self::Link => LINK_EXIT_CODE,
+            Self::Lint => LINT_EXIT_CODE,
+            Self::Format(_) => FORMAT_EXIT_CODE,
+            Self::App(code) => *code,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(message) | Self::Io(message) => write!(f, "{message}"),
+            Self::Build(message) => write!(f, "Build error : {message}"),
+            Self::Compile => write!(f, "Build failed, with compilation errors"),
+            Self::Link => write!(f, "Build failed, with link errors"),
+            Self::Lint => write!(f, "Finished, with errors"),
+            Self::Format(count) => write!(f, "Finished, {count} file(s) not formatted"),
+            Self::App(code) => write!(f, "Your app exited with status {code}"),
+        }
+    }
+}
+
+/// Converts a process exit status to a non-zero exit code, processes killed by a signal
+/// are reported as 128 + signal number like shells do
+fn status_code(status: ExitStatus) -> u8 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(signal) = status.signal() {
+            return u8::try_from(128 + signal).unwrap_or(u8::MAX);
+        }
+    }
+
+    match status.code() {
+        Some(0) | None => GENERIC_EXIT_CODE,
+        Some(code) => u8::try_from(code)
+            .ok()
+            .filter(|&code| code != 0)
+            .unwrap_or(u8::MAX),
+    }
+}
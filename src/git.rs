@@ -0,0 +1,66 @@
+use std::process::Command;
+
+fn git(args: &[&str]) -> Result<Option<String>, String> {
+    let mut git_command = Command::new("git");
+    git_command.args(args);
+
+    match git_command.output() {
+        Ok(git_output) => {
+            if git_output.status.success() {
+                Ok(Some(
+                    String::from_utf8_lossy(&git_output.stdout).into_owned(),
+                ))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(error) => Err(format!("Can't run git : {error}")),
+    }
+}
+
+/// Returns true if git reports uncommitted changes to tracked files, projects outside of a git
+/// repository are considered clean. Untracked files (like the build directory) are ignored as
+/// fixes don't modify them
+pub fn has_uncommitted_changes() -> Result<bool, String> {
+    Ok(git(&["status", "--porcelain", "--untracked-files=no"])?
+        .is_some_and(|status| !status.is_empty()))
+}
+
+/// Lists the files of the current directory that were modified since `base` (the last commit
+/// by default) or are untracked, paths are relative to the current directory
+pub fn changed_files(base: Option<&str>) -> Result<Vec<String>, String> {
+    let base = base.unwrap_or("HEAD");
+
+    let modified = git(&["diff", "--name-only", "--relative", "--diff-filter=d", base])?
+        .ok_or_else(|| format!("Can't list files changed since {base}, is it a git revision ?"))?;
+
+    let untracked = git(&["ls-files", "--others", "--exclude-standard"])?
+        .ok_or_else(|| "Can't list untracked files".to_owned())?;
+
+    let mut files = Vec::new();
+
+    for file in modified.lines().chain(untracked.lines()) {
+        if !files.iter().any(|f| f == file) {
+            files.push(file.to_owned());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Lists the untracked files of `dirs` ignored by `.gitignore` files, nothing is ignored
+/// outside of a git repository
+pub fn ignored_files(dirs: &[&str]) -> Result<Vec<String>, String> {
+    let mut args = vec![
+        "ls-files",
+        "--others",
+        "--ignored",
+        "--exclude-standard",
+        "--",
+    ];
+    args.extend(dirs);
+
+    Ok(git(&args)?.map_or_else(Vec::new, |ignored| {
+        ignored.lines().map(str::to_owned).collect()
+    }))
+}
@@ -0,0 +1,89 @@
+/// Matches a path relative to the project root against a glob pattern : `*` matches any part
+/// of a file or directory name, `?` a single character and a `**` component any number of
+/// directories (`src/vendor/**`, `**/*_win.cpp`)
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = components(pattern);
+    let path = components(path);
+
+    match_components(&pattern, &path)
+}
+
+/// Splits a path in its components, ignoring `.` components and separators of both platforms
+fn components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect()
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", pattern_rest)) => {
+            (0..=path.len()).any(|skipped| match_components(pattern_rest, &path[skipped..]))
+        }
+        Some((pattern_component, pattern_rest)) => {
+            path.split_first().is_some_and(|(component, path_rest)| {
+                match_name(
+                    &pattern_component.chars().collect::<Vec<_>>(),
+                    &component.chars().collect::<Vec<_>>(),
+                ) && match_components(pattern_rest, path_rest)
+            })
+        }
+    }
+}
+
+fn match_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', pattern_rest)) => {
+            (0..=name.len()).any(|skipped| match_name(pattern_rest, &name[skipped..]))
+        }
+        Some(('?', pattern_rest)) => !name.is_empty() && match_name(pattern_rest, &name[1..]),
+        Some((c, pattern_rest)) => name
+            .split_first()
+            .is_some_and(|(name_c, name_rest)| name_c == c && match_name(pattern_rest, name_rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn directory_tree() {
+        assert!(matches("src/vendor/**", "src/vendor/lib.cpp"));
+        assert!(matches("src/vendor/**", "src/vendor/deep/nested/lib.cpp"));
+        assert!(!matches("src/vendor/**", "src/main.cpp"));
+        assert!(!matches("src/vendor/**", "src/vendored/lib.cpp"));
+    }
+
+    #[test]
+    fn any_directory() {
+        assert!(matches("**/*_win.cpp", "io_win.cpp"));
+        assert!(matches("**/*_win.cpp", "src/platform/io_win.cpp"));
+        assert!(!matches("**/*_win.cpp", "src/platform/io_win.cpp.bak"));
+        assert!(!matches("**/*_win.cpp", "src/platform/io_linux.cpp"));
+    }
+
+    #[test]
+    fn wildcards_stay_in_a_name() {
+        assert!(matches("src/*.cpp", "src/main.cpp"));
+        assert!(!matches("src/*.cpp", "src/io/main.cpp"));
+        assert!(matches("src/util?.c", "src/util2.c"));
+        assert!(!matches("src/util?.c", "src/util.c"));
+    }
+
+    #[test]
+    fn current_directory_prefix() {
+        assert!(matches("./src/vendor/**", "src/vendor/lib.cpp"));
+        assert!(matches("src/vendor/**", "./src/vendor/lib.cpp"));
+        assert!(matches("src/./main.cpp", "src/main.cpp"));
+    }
+
+    #[test]
+    fn backslash_separators() {
+        assert!(matches("src/vendor/**", "src\\vendor\\lib.cpp"));
+        assert!(matches("src\\vendor\\*.cpp", "src/vendor/lib.cpp"));
+        assert!(matches("**/*_win.cpp", ".\\src\\io_win.cpp"));
+    }
+}
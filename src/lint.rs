@@ -0,0 +1,531 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::parallel_runner::parallel_run;
+
+const SEVERITIES: &[(&str, Severity)] = &[
+    (": fatal error: ", Severity::Error),
+    (": error: ", Severity::Error),
+    (": warning: ", Severity::Warning),
+    (": note: ", Severity::Note),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A diagnostic reported by the linter, with the notes attached to it
+#[derive(Serialize)]
+pub struct Diagnostic {
+    /// Path relative to the project root when the file is part of the project
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub severity: Severity,
+
+    /// Name of the check that emitted the diagnostic, compiler errors don't have one
+    pub check: Option<String>,
+    pub message: String,
+
+    /// Diagnostic as printed by the linter, including the source snippet and notes
+    #[serde(skip)]
+    pub text: String,
+
+    /// Same as `text`, with the colors of the linter output
+    #[serde(skip)]
+    pub colored_text: String,
+}
+
+#[derive(Default)]
+pub struct LintReport {
+    /// Unique diagnostics of every linted file, sorted by location
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Linter error messages of the files it failed to lint
+    pub failures: Vec<String>,
+}
+
+struct LintTask {
+    linter: String,
+    header_filter: String,
+    root: PathBuf,
+    file: String,
+    compile_flags: Vec<String>,
+
+    /// Checks given on the command line, when they are not read from generated `.clang-tidy`
+    /// files
+    checks: Option<String>,
+
+    /// File the suggested fixes are exported to
+    fixes_file: Option<String>,
+    color: bool,
+}
+
+/// Lints every translation unit (given with its compile flags) in its own linter process,
+/// diagnostics in the headers of `project_dirs` are reported as well. If `fixes_dir` is given,
+/// the fixes suggested for each translation unit are exported there to be applied by
+/// `apply_fixes`
+pub fn lint(
+    linter: &str,
+    project_dirs: &[&str],
+    files: Vec<(String, Vec<String>)>,
+    checks: Option<&str>,
+    fixes_dir: Option<&str>,
+    color: bool,
+) -> Result<LintReport, String> {
+    let root = std::fs::canonicalize(".")
+        .map_err(|error| format!("Can't find the project directory : {error}"))?;
+
+    let header_filter = header_filter(&root, project_dirs);
+
+    if let Some(fixes_dir) = fixes_dir {
+        // Fixes of a previous run must not be applied again
+        if Path::new(fixes_dir).is_dir() {
+            std::fs::remove_dir_all(fixes_dir)
+                .map_err(|error| format!("Can't remove {fixes_dir} directory : {error}"))?;
+        }
+
+        std::fs::create_dir_all(fixes_dir)
+            .map_err(|error| format!("Can't create {fixes_dir} directory : {error}"))?;
+    }
+
+    let mut tasks = Vec::new();
+
+    for (index, (file, compile_flags)) in files.into_iter().enumerate() {
+        tasks.push(LintTask {
+            linter: linter.to_owned(),
+            header_filter: header_filter.clone(),
+            root: root.clone(),
+            file,
+            compile_flags,
+            checks: checks.map(str::to_owned),
+            fixes_file: fixes_dir.map(|dir| format!("{dir}/{index}.yaml")),
+            color,
+        });
+    }
+
+    let mut report = LintReport::default();
+
+    // Headers are linted with every translation unit including them
+    let mut seen = HashSet::new();
+
+    for result in parallel_run(tasks, lint_file) {
+        let (diagnostics, failure) = result?;
+
+        for diagnostic in diagnostics {
+            if seen.insert(diagnostic.text.clone()) {
+                report.diagnostics.push(diagnostic);
+            }
+        }
+
+        if let Some(failure) = failure {
+            report.failures.push(failure);
+        }
+    }
+
+    report.diagnostics.sort_by(|a, b| {
+        (&a.file, a.line, a.column, &a.message).cmp(&(&b.file, b.line, b.column, &b.message))
+    });
+
+    Ok(report)
+}
+
+fn lint_file(task: LintTask) -> Result<(Vec<Diagnostic>, Option<String>), String> {
+    let LintTask {
+        linter,
+        header_filter,
+        root,
+        file,
+        compile_flags,
+        checks,
+        fixes_file,
+        color,
+    } = task;
+
+    let mut lint_command = Command::new(linter);
+
+    lint_command.arg(&file);
+    lint_command.arg(format!("-header-filter={header_filter}"));
+    if let Some(checks) = &checks {
+        lint_command.arg(format!("-checks={checks}"));
+    }
+    if let Some(fixes_file) = &fixes_file {
+        lint_command.arg(format!("--export-fixes={fixes_file}"));
+    }
+    if color {
+        lint_command.arg("--use-color");
+    }
+    lint_command.arg("--");
+    lint_command.args(compile_flags);
+
+    let lint_output = lint_command
+        .output()
+        .map_err(|error| format!("Can't run linter : {error}"))?;
+
+    let diagnostics = parse_diagnostics(&String::from_utf8_lossy(&lint_output.stdout), &root);
+
+    let failure = if lint_output.status.success() {
+        None
+    } else {
+        // Fixes computed on code that doesn't compile can't be trusted
+        if let Some(fixes_file) = &fixes_file {
+            let _ = std::fs::remove_file(fixes_file);
+        }
+
+        Some(format!(
+            "Can't lint {file} : {}",
+            String::from_utf8_lossy(&lint_output.stderr)
+                .lines()
+                .filter(|line| !line.ends_with(" generated."))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    };
+
+    Ok((diagnostics, failure))
+}
+
+/// Applies the fixes exported by `lint` with clang-apply-replacements. The same header fix is
+/// exported by every translation unit including the header, identical replacements are merged
+/// and conflicting ones are reported without modifying the file
+pub fn apply_fixes(linter: &str, fixes_dir: &str) -> Result<bool, String> {
+    // clang-tidy-17 comes with clang-apply-replacements-17
+    let applier = linter.replace("clang-tidy", "clang-apply-replacements");
+    let applier = if applier == linter {
+        "clang-apply-replacements".to_owned()
+    } else {
+        applier
+    };
+
+    let mut apply_command = Command::new(&applier);
+
+    apply_command.arg("--remove-change-desc-files");
+    apply_command.arg(fixes_dir);
+
+    match apply_command.status() {
+        Ok(exit_status) => Ok(exit_status.success()),
+        Err(error) => Err(format!("Can't run {applier} : {error}")),
+    }
+}
+
+pub fn to_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string_pretty(diagnostics).unwrap_or_default()
+}
+
+/// Static Analysis Results Interchange Format document, as ingested by code review tools
+pub fn to_sarif(diagnostics: &[Diagnostic], linter: &str) -> String {
+    let mut rules = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.check.as_deref())
+        .collect::<Vec<_>>();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let uri = if Path::new(&diagnostic.file).is_absolute() {
+                format!("file://{}", diagnostic.file)
+            } else {
+                diagnostic.file.replace('\\', "/")
+            };
+
+            json!({
+                "ruleId": diagnostic.check.as_deref().unwrap_or("clang-diagnostic"),
+                "level": match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Note => "note",
+                },
+                "message": { "text": diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri },
+                        "region": {
+                            "startLine": diagnostic.line,
+                            "startColumn": diagnostic.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": linter,
+                    "rules": rules.iter().map(|rule| json!({ "id": rule })).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&sarif).unwrap_or_default()
+}
+
+/// Regex matching the headers inside the project directories, whether the linter reports
+/// them with a relative or an absolute path
+fn header_filter(root: &Path, project_dirs: &[&str]) -> String {
+    let root = regex_escape(&root.to_string_lossy());
+    let dirs = project_dirs
+        .iter()
+        .map(|dir| regex_escape(dir))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    format!("^(\\./|{root}/)?({dirs})/")
+}
+
+fn regex_escape(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Splits the linter output in diagnostics, notes and source snippets are attached to the
+/// diagnostic preceding them
+fn parse_diagnostics(output: &str, root: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for colored_line in output.lines() {
+        let line = &strip_colors(colored_line);
+
+        match parse_diagnostic_line(line, root) {
+            Some(mut diagnostic) if diagnostic.severity != Severity::Note => {
+                diagnostic.colored_text = strip_colored_root(colored_line, root);
+                diagnostics.push(diagnostic);
+            }
+            _ => {
+                // Summary lines printed before the first diagnostic are dropped
+                if let Some(diagnostic) = diagnostics.last_mut() {
+                    diagnostic.text.push('\n');
+                    diagnostic.text.push_str(strip_root(line, root));
+                    diagnostic.colored_text.push('\n');
+                    diagnostic
+                        .colored_text
+                        .push_str(&strip_colored_root(colored_line, root));
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Removes the ANSI escape sequences of a line
+fn strip_colors(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Sequences end with a letter, like "\x1b[1;31m"
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// Same as `strip_root`, the path may follow a color sequence
+fn strip_colored_root(line: &str, root: &Path) -> String {
+    let root = format!("{}/", root.to_string_lossy());
+
+    if strip_colors(line).starts_with(&root) {
+        line.replacen(&root, "", 1)
+    } else {
+        line.to_owned()
+    }
+}
+
+/// Makes the paths starting a line of the linter output relative to the project root
+fn strip_root<'a>(line: &'a str, root: &Path) -> &'a str {
+    line.strip_prefix(&format!("{}/", root.to_string_lossy()))
+        .unwrap_or(line)
+}
+
+/// Parses a `file:line:column: severity: message [check]` line
+fn parse_diagnostic_line(line: &str, root: &Path) -> Option<Diagnostic> {
+    let (location_end, marker, severity) = SEVERITIES
+        .iter()
+        .filter_map(|&(marker, severity)| {
+            line.find(marker)
+                .map(|position| (position, marker, severity))
+        })
+        .min_by_key(|&(position, _, _)| position)?;
+
+    let mut location = line[..location_end].rsplitn(3, ':');
+    let column = location.next()?.parse().ok()?;
+    let line_number = location.next()?.parse().ok()?;
+    let file = location.next()?;
+
+    let message = &line[location_end + marker.len()..];
+
+    let (message, check) = match message.rsplit_once(" [") {
+        Some((text, check)) if check.ends_with(']') && !check.contains(' ') => {
+            (text, Some(check.trim_end_matches(']').to_owned()))
+        }
+        _ => (message, None),
+    };
+
+    let file = Path::new(file).strip_prefix(root).map_or_else(
+        |_| file.to_owned(),
+        |relative| relative.to_string_lossy().into_owned(),
+    );
+
+    let text = strip_root(line, root).to_owned();
+
+    Some(Diagnostic {
+        file,
+        line: line_number,
+        column,
+        severity,
+        check,
+        message: message.to_owned(),
+        colored_text: text.clone(),
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(output: &str) -> Vec<Diagnostic> {
+        parse_diagnostics(output, Path::new("/home/dev/project"))
+    }
+
+    #[test]
+    fn absolute_and_relative_paths() {
+        let diagnostics = parse(
+            "/home/dev/project/src/main.cpp:3:5: warning: unused variable 'x' [misc-unused]\n\
+             include/a.h:10:1: error: unknown type name 'foo' [clang-diagnostic-error]\n\
+             /usr/include/stdio.h:1:1: warning: system header [misc-system]",
+        );
+
+        let locations = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.file.as_str(), diagnostic.line, diagnostic.column))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            [
+                ("src/main.cpp", 3, 5),
+                ("include/a.h", 10, 1),
+                ("/usr/include/stdio.h", 1, 1)
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].text,
+            "src/main.cpp:3:5: warning: unused variable 'x' [misc-unused]"
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+    }
+
+    #[test]
+    fn windows_drive_letter() {
+        let diagnostics = parse(r"C:\project\src\main.cpp:12:7: warning: message [misc-check]");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, r"C:\project\src\main.cpp");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (12, 7));
+        assert_eq!(diagnostics[0].check.as_deref(), Some("misc-check"));
+    }
+
+    #[test]
+    fn bracket_in_message() {
+        let diagnostics = parse(
+            "src/a.cpp:1:1: error: no matching function [with T = int]\n\
+             src/a.cpp:2:1: warning: index [0] out of range [misc-range]",
+        );
+
+        assert_eq!(diagnostics[0].check, None);
+        assert_eq!(
+            diagnostics[0].message,
+            "no matching function [with T = int]"
+        );
+        assert_eq!(diagnostics[1].check.as_deref(), Some("misc-range"));
+        assert_eq!(diagnostics[1].message, "index [0] out of range");
+    }
+
+    #[test]
+    fn notes_are_attached() {
+        let diagnostics = parse(
+            "2 warnings generated.\n\
+             src/a.cpp:4:3: warning: use after move [bugprone-use-after-move]\n\
+             \x20   4 |   use(x);\n\
+             src/a.cpp:3:3: note: move occurred here\n\
+             src/b.cpp:1:1: warning: other [misc-other]",
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].text,
+            "src/a.cpp:4:3: warning: use after move [bugprone-use-after-move]\n\
+             \x20   4 |   use(x);\n\
+             src/a.cpp:3:3: note: move occurred here"
+        );
+        assert_eq!(
+            diagnostics[1].text,
+            "src/b.cpp:1:1: warning: other [misc-other]"
+        );
+    }
+
+    #[test]
+    fn colored_output() {
+        let diagnostics = parse(
+            "\x1b[1m/home/dev/project/src/main.cpp:3:5: \x1b[0m\x1b[0;1;35mwarning: \
+             \x1b[0m\x1b[1mmessage [misc-check]\x1b[0m",
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "src/main.cpp");
+        assert_eq!(diagnostics[0].message, "message");
+        assert_eq!(diagnostics[0].check.as_deref(), Some("misc-check"));
+        assert_eq!(
+            diagnostics[0].text,
+            "src/main.cpp:3:5: warning: message [misc-check]"
+        );
+        assert_eq!(
+            diagnostics[0].colored_text,
+            "\x1b[1msrc/main.cpp:3:5: \x1b[0m\x1b[0;1;35mwarning: \
+             \x1b[0m\x1b[1mmessage [misc-check]\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn strip_color_sequences() {
+        assert_eq!(strip_colors("\x1b[1;31merror\x1b[0m: text"), "error: text");
+        assert_eq!(strip_colors("plain"), "plain");
+    }
+}
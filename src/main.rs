@@ -1,58 +1,158 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 
+mod baseline;
+mod clang_tidy;
+mod color;
+mod compilation_database;
+mod debugger;
+mod depfile;
+mod error;
+mod git;
+mod glob;
+mod lint;
 mod parallel_runner;
+mod target;
+mod toolchain;
+mod watch;
 
 use std::{
+    cell::OnceCell,
+    collections::HashMap,
     fs::create_dir_all,
     io::{self, Write},
-    path::Path,
-    process::Command,
+    path::{Component, Path, PathBuf},
+    process::{Child, Command, ExitCode},
+    time::SystemTime,
 };
 
-use clap::{Parser, Subcommand};
+use clang_tidy::{TidyConfig, TidyOverride};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use color::{ColorChoice, Stream};
+use compilation_database::CompileCommand;
+use debugger::DebuggerKind;
+use error::Error;
+use lint::{LintReport, Severity};
 use parallel_runner::parallel_run;
+use target::Target;
 use toml::{map::Map, Table, Value};
+use toolchain::{Family, Toolchain};
 use walkdir::WalkDir;
+use watch::ChangeWatcher;
 
 const CONFIG_FILE: &str = "Embargo.toml";
+const USER_CONFIG_DIR: &str = "embargo";
+const USER_CONFIG_FILE: &str = "config.toml";
+const CONFIG_OPTION_ORIGIN: &str = "--config option";
 const COMPILE_FLAGS_FILE: &str = "compile_flags.txt";
+const CLANG_FORMAT_FILE: &str = ".clang-format";
+const LINT_BASELINE_FILE: &str = "lint-baseline.json";
 
 const COMPILER_KEY: &str = "compiler";
+const C_COMPILER_KEY: &str = "c-compiler";
 const DEBUGGER_KEY: &str = "debugger";
+const DEBUGGER_KIND_KEY: &str = "debugger-kind";
 const LINTER_KEY: &str = "linter";
+const FORMATTER_KEY: &str = "formatter";
+const SYSROOT_KEY: &str = "sysroot";
+const LTO_KEY: &str = "lto";
+const SANITIZERS_KEY: &str = "sanitizers";
 
 const FLAGS_KEY: &str = "flags";
+const C_FLAGS_KEY: &str = "c-flags";
+const CXX_FLAGS_KEY: &str = "cxx-flags";
+const ASM_FLAGS_KEY: &str = "asm-flags";
+const C_STANDARD_KEY: &str = "c-standard";
+const CXX_STANDARD_KEY: &str = "cxx-standard";
+const STD_KEY: &str = "std";
+const WARNINGS_KEY: &str = "warnings";
+const WARNINGS_AS_ERRORS_KEY: &str = "warnings-as-errors";
+const DEFINES_KEY: &str = "defines";
+const INCLUDE_DIRS_KEY: &str = "include-dirs";
+
+const C_EXTENSIONS_KEY: &str = "c-extensions";
+const CXX_EXTENSIONS_KEY: &str = "cxx-extensions";
+const ASM_EXTENSIONS_KEY: &str = "asm-extensions";
+const HEADER_EXTENSIONS_KEY: &str = "header-extensions";
+const EXCLUDE_KEY: &str = "exclude";
+const RESPECT_GITIGNORE_KEY: &str = "respect-gitignore";
 const DEBUG_FLAGS_KEY: &str = "debug-flags";
 const RELEASE_FLAGS_KEY: &str = "release-flags";
 const LINKER_FLAGS_KEY: &str = "linker-flags";
+const FLAGS_OVERRIDE_KEY: &str = "override";
+const TARGET_KEY: &str = "target";
+
+/// Prefix of the environment variables setting a configuration key, `EMBARGO_C_FLAGS` sets
+/// `c-flags`
+const ENVIRONMENT_PREFIX: &str = "EMBARGO_";
+
+/// Conventional environment variables, with the key they set and whether their value is a list
+/// of flags separated by whitespace
+const ENVIRONMENT_VARIABLES: &[(&str, &str, bool)] = &[
+    ("CC", C_COMPILER_KEY, false),
+    ("CXX", COMPILER_KEY, false),
+    ("CFLAGS", C_FLAGS_KEY, true),
+    ("CXXFLAGS", CXX_FLAGS_KEY, true),
+    ("LDFLAGS", LINKER_FLAGS_KEY, true),
+];
+
+/// List settings accepting a whitespace separated value in `EMBARGO_*` variables
+const ENVIRONMENT_LIST_KEYS: &[&str] = &[
+    FLAGS_KEY,
+    C_FLAGS_KEY,
+    CXX_FLAGS_KEY,
+    ASM_FLAGS_KEY,
+    DEBUG_FLAGS_KEY,
+    RELEASE_FLAGS_KEY,
+    LINKER_FLAGS_KEY,
+    INCLUDE_DIRS_KEY,
+    SANITIZERS_KEY,
+    EXCLUDE_KEY,
+];
 
 const LINTER_CHECKS_KEY: &str = "linter-checks";
+const LINTER_WARNINGS_AS_ERRORS_KEY: &str = "linter-warnings-as-errors";
+const LINTER_CHECK_OPTIONS_KEY: &str = "linter-check-options";
+const LINTER_OVERRIDE_KEY: &str = "linter-override";
+
+const OVERRIDE_PATH_KEY: &str = "path";
+const OVERRIDE_CHECKS_KEY: &str = "checks";
+const OVERRIDE_WARNINGS_AS_ERRORS_KEY: &str = "warnings-as-errors";
+const OVERRIDE_CHECK_OPTIONS_KEY: &str = "check-options";
+const OVERRIDE_FLAGS_KEY: &str = "flags";
+const OVERRIDE_REMOVE_FLAGS_KEY: &str = "remove-flags";
 
 const DEFAULT_COMPILER: &str = "clang++";
+const DEFAULT_C_COMPILER: &str = "clang";
 const DEFAULT_DEBUGGER: &str = "lldb";
 const DEFAULT_LINTER: &str = "clang-tidy";
+const DEFAULT_FORMATTER: &str = "clang-format";
 
 const DEFAULT_FLAGS: &[&str] = &["-Wall", "-Wextra", "-pedantic"];
+const DEFAULT_C_FLAGS: &[&str] = &[];
+const DEFAULT_CXX_FLAGS: &[&str] = &[];
+const DEFAULT_ASM_FLAGS: &[&str] = &[];
 const DEFAULT_DEBUG_FLAGS: &[&str] = &["-g"];
 const DEFAULT_RELEASE_FLAGS: &[&str] = &["-O2"];
 const DEFAULT_LINKER_FLAGS: &[&str] = &[];
 
-const DEFAULT_LINTER_CHECKS: &str = "clang-analyzer-*";
+const DEFAULT_C_EXTENSIONS: &[&str] = &[".c"];
+const DEFAULT_CXX_EXTENSIONS: &[&str] = &[".cpp", ".cc", ".cxx", ".c++"];
+const DEFAULT_ASM_EXTENSIONS: &[&str] = &[".S", ".s"];
+const DEFAULT_HEADER_EXTENSIONS: &[&str] = &[".h", ".hpp", ".hh", ".hxx"];
+
+const DEFAULT_LINTER_CHECKS: &[&str] = &["clang-analyzer-*"];
 
 const SRC_DIR: &str = "src";
 const INCLUDE_DIR: &str = "include";
+
+/// Only watched, embargo has no test command yet
+const TESTS_DIR: &str = "tests";
 const BUILD_DIR: &str = "build";
 
 const DEBUG_BUILD_SUBDIR: &str = "debug";
 const RELEASE_BUILD_SUBDIR: &str = "release";
-
-#[cfg(target_os = "linux")]
-static EXE_EXTENSION: &str = "";
-#[cfg(target_os = "macos")]
-static EXE_EXTENSION: &str = "";
-#[cfg(target_os = "windows")]
-static EXE_EXTENSION: &str = ".exe";
+const LINT_FIXES_SUBDIR: &str = "lint-fixes";
 
 const HELLO_WORLD: &str = r#"#include <iostream>
 
@@ -62,19 +162,190 @@ int main() {
 }
 "#;
 
+const DEFAULT_CLANG_FORMAT: &str = "BasedOnStyle: LLVM
+IndentWidth: 4
+";
+
 const SEPARATOR: char = std::path::MAIN_SEPARATOR;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WarningLevel {
+    None,
+    Default,
+    Strict,
+}
+
+impl WarningLevel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "default" => Some(Self::Default),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+
+    const fn flags(self) -> &'static [&'static str] {
+        match self {
+            Self::None => &["-w"],
+            Self::Default => &["-Wall", "-Wextra", "-pedantic"],
+            Self::Strict => &["-Wall", "-Wextra", "-pedantic", "-Wshadow", "-Wconversion"],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Language {
+    C,
+    Cxx,
+
+    /// Assembly, compiled through the C compiler driver
+    Asm,
+}
+
+impl Language {
+    /// Language of a standard name like `c11`, `gnu17` or `c++20`
+    fn of_standard(standard: &str) -> Self {
+        if standard.contains("++") {
+            Self::Cxx
+        } else {
+            Self::C
+        }
+    }
+}
+
+/// Flags removed from and added to the flags of the sources matching a glob pattern
+struct FlagOverride {
+    path: String,
+    flags: Vec<String>,
+    remove_flags: Vec<String>,
+}
+
+#[allow(clippy::struct_excessive_bools)]
 struct Config {
+    /// Platform the app is built for, its `[target]` sections are merged in the configuration
+    target: Target,
+
+    /// Source of the keys that are not set to their default value
+    origins: HashMap<String, String>,
+
+    /// The target was given on the command line, its triple is passed to the compiler and it
+    /// has its own build directory
+    cross_compiling: bool,
+    sysroot: Option<String>,
+
+    /// C++ compiler, also used to link the app
     compiler: String,
+    c_compiler: String,
     debugger: String,
+    debugger_kind: DebuggerKind,
     linter: String,
+    formatter: String,
 
     flags: Vec<String>,
+    c_flags: Vec<String>,
+    cxx_flags: Vec<String>,
+    asm_flags: Vec<String>,
+    c_standard: Option<String>,
+    cxx_standard: Option<String>,
+
+    /// Warnings enabled in addition to the ones of `flags`
+    warnings: Option<WarningLevel>,
+    warnings_as_errors: bool,
+    defines: Vec<(String, Option<String>)>,
+
+    /// Include directories searched after `include`
+    include_dirs: Vec<String>,
+
     debug_flags: Vec<String>,
     release_flags: Vec<String>,
     linker_flags: Vec<String>,
+    flag_overrides: Vec<FlagOverride>,
+
+    /// Link time optimization of release builds
+    lto: bool,
 
-    linter_checks: String,
+    /// Sanitizers of debug builds (address, undefined, ...)
+    sanitizers: Vec<String>,
+
+    /// Detected when first needed, as it runs the compilers
+    cxx_toolchain: OnceCell<Toolchain>,
+    c_toolchain: OnceCell<Toolchain>,
+
+    c_extensions: Vec<String>,
+    cxx_extensions: Vec<String>,
+    asm_extensions: Vec<String>,
+    header_extensions: Vec<String>,
+
+    /// Glob patterns of the files ignored by every command
+    exclude: Vec<String>,
+    respect_gitignore: bool,
+
+    linter_checks: TidyConfig,
+    linter_overrides: Vec<TidyOverride>,
+}
+
+impl Config {
+    /// Source of the value of the first key that is set
+    fn origin(&self, keys: &[&str]) -> &str {
+        keys.iter()
+            .find_map(|key| self.origins.get(*key))
+            .map_or("default", String::as_str)
+    }
+
+    /// Toolchain of the compiler of a language
+    fn toolchain(&self, language: Language) -> Toolchain {
+        match language {
+            Language::C | Language::Asm => *self
+                .c_toolchain
+                .get_or_init(|| Toolchain::detect(&self.c_compiler)),
+            Language::Cxx => *self
+                .cxx_toolchain
+                .get_or_init(|| Toolchain::detect(&self.compiler)),
+        }
+    }
+
+    fn language_of(&self, source: &str) -> Language {
+        let matches = |extensions: &[String]| {
+            extensions
+                .iter()
+                .any(|extension| source.ends_with(extension.as_str()))
+        };
+
+        if matches(&self.c_extensions) {
+            Language::C
+        } else if matches(&self.asm_extensions) {
+            Language::Asm
+        } else {
+            Language::Cxx
+        }
+    }
+
+    /// Returns true if the file matches one of the `exclude` patterns
+    fn is_excluded(&self, file: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| glob::matches(pattern, file))
+    }
+
+    fn source_extensions(&self) -> Vec<&str> {
+        self.c_extensions
+            .iter()
+            .chain(&self.cxx_extensions)
+            .chain(&self.asm_extensions)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Extensions of the files handled by the formatter
+    fn code_extensions(&self) -> Vec<&str> {
+        self.header_extensions
+            .iter()
+            .chain(&self.c_extensions)
+            .chain(&self.cxx_extensions)
+            .map(String::as_str)
+            .collect()
+    }
 }
 
 fn read_string_key(toml: &Map<String, Value>, key_name: &str) -> Result<Option<String>, String> {
@@ -86,6 +357,15 @@ fn read_string_key(toml: &Map<String, Value>, key_name: &str) -> Result<Option<S
     })
 }
 
+fn read_bool_key(toml: &Map<String, Value>, key_name: &str) -> Result<Option<bool>, String> {
+    toml.get(key_name).map_or(Ok(None), |value| {
+        value.as_bool().map_or_else(
+            || Err(format!("{key_name} value must be a boolean")),
+            |boolean| Ok(Some(boolean)),
+        )
+    })
+}
+
 fn read_string_list_key(
     toml: &Map<String, Value>,
     key_name: &str,
@@ -111,6 +391,183 @@ fn read_string_list_key(
     }
 }
 
+fn read_warnings_key(toml: &Map<String, Value>) -> Result<Option<WarningLevel>, String> {
+    read_string_key(toml, WARNINGS_KEY)?.map_or(Ok(None), |name| {
+        WarningLevel::from_name(&name).map_or_else(
+            || {
+                Err(format!(
+                    "{WARNINGS_KEY} value must be \"strict\", \"default\" or \"none\""
+                ))
+            },
+            |level| Ok(Some(level)),
+        )
+    })
+}
+
+/// Reads the preprocessor definitions, `true` defines a macro without value
+fn read_defines_key(toml: &Map<String, Value>) -> Result<Vec<(String, Option<String>)>, String> {
+    let Some(value) = toml.get(DEFINES_KEY) else {
+        return Ok(Vec::new());
+    };
+
+    let Some(table) = value.as_table() else {
+        return Err(format!("{DEFINES_KEY} value must be a table"));
+    };
+
+    let mut defines = Vec::new();
+
+    for (name, value) in table {
+        let value = match value {
+            Value::Boolean(true) => None,
+            Value::String(string) => Some(string.clone()),
+            Value::Integer(integer) => Some(integer.to_string()),
+            _ => {
+                return Err(format!(
+                    "{DEFINES_KEY}.{name} value must be a string, an integer or true"
+                ))
+            }
+        };
+
+        defines.push((name.clone(), value));
+    }
+
+    Ok(defines)
+}
+
+fn read_debugger_kind_key(
+    toml: &Map<String, Value>,
+    debugger: &str,
+) -> Result<DebuggerKind, String> {
+    read_string_key(toml, DEBUGGER_KIND_KEY)?.map_or_else(
+        || Ok(DebuggerKind::detect(debugger)),
+        |name| {
+            DebuggerKind::from_name(&name).ok_or_else(|| {
+                format!("{DEBUGGER_KIND_KEY} value must be \"lldb\", \"gdb\" or \"generic\"")
+            })
+        },
+    )
+}
+
+/// Reads a list of check patterns, written either as an array or as a comma separated string
+fn read_pattern_list_key(
+    toml: &Map<String, Value>,
+    key_name: &str,
+) -> Result<Option<Vec<String>>, String> {
+    match toml.get(key_name) {
+        Some(Value::String(patterns)) => Ok(Some(
+            patterns
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        )),
+        Some(Value::Array(_)) | None => read_string_list_key(toml, key_name),
+        Some(_) => Err(format!(
+            "{key_name} value must be a string or an array of string"
+        )),
+    }
+}
+
+fn read_check_options_key(
+    toml: &Map<String, Value>,
+    key_name: &str,
+) -> Result<Option<Vec<(String, String)>>, String> {
+    let Some(value) = toml.get(key_name) else {
+        return Ok(None);
+    };
+
+    let Some(table) = value.as_table() else {
+        return Err(format!("{key_name} value must be a table"));
+    };
+
+    let mut options = Vec::new();
+
+    for (option, value) in table {
+        let value = match value {
+            Value::String(string) => string.clone(),
+            Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => value.to_string(),
+            _ => {
+                return Err(format!(
+                    "{key_name}.{option} value must be a string, a number or a boolean"
+                ))
+            }
+        };
+
+        options.push((option.clone(), value));
+    }
+
+    Ok(Some(options))
+}
+
+fn read_linter_overrides_key(toml: &Map<String, Value>) -> Result<Vec<TidyOverride>, String> {
+    let Some(value) = toml.get(LINTER_OVERRIDE_KEY) else {
+        return Ok(Vec::new());
+    };
+
+    let error = || format!("{LINTER_OVERRIDE_KEY} value must be an array of tables");
+
+    let mut overrides = Vec::new();
+
+    for override_value in value.as_array().ok_or_else(error)? {
+        let override_table = override_value.as_table().ok_or_else(error)?;
+
+        let path = read_string_key(override_table, OVERRIDE_PATH_KEY)?
+            .ok_or_else(|| format!("{LINTER_OVERRIDE_KEY} must have a {OVERRIDE_PATH_KEY} key"))?;
+
+        overrides.push(TidyOverride {
+            path,
+            config: TidyConfig {
+                checks: read_pattern_list_key(override_table, OVERRIDE_CHECKS_KEY)?
+                    .unwrap_or_default(),
+                warnings_as_errors: read_pattern_list_key(
+                    override_table,
+                    OVERRIDE_WARNINGS_AS_ERRORS_KEY,
+                )?
+                .unwrap_or_default(),
+                check_options: read_check_options_key(override_table, OVERRIDE_CHECK_OPTIONS_KEY)?
+                    .unwrap_or_default(),
+            },
+        });
+    }
+
+    Ok(overrides)
+}
+
+fn read_string_list_key_or(
+    toml: &Map<String, Value>,
+    key_name: &str,
+    default: &[&str],
+) -> Result<Vec<String>, String> {
+    Ok(read_string_list_key(toml, key_name)?.unwrap_or_else(|| to_owned_string_vec(default)))
+}
+
+fn read_flag_overrides_key(toml: &Map<String, Value>) -> Result<Vec<FlagOverride>, String> {
+    let Some(value) = toml.get(FLAGS_OVERRIDE_KEY) else {
+        return Ok(Vec::new());
+    };
+
+    let error = || format!("{FLAGS_OVERRIDE_KEY} value must be an array of tables");
+
+    let mut overrides = Vec::new();
+
+    for override_value in value.as_array().ok_or_else(error)? {
+        let override_table = override_value.as_table().ok_or_else(error)?;
+
+        let path = read_string_key(override_table, OVERRIDE_PATH_KEY)?
+            .ok_or_else(|| format!("{FLAGS_OVERRIDE_KEY} must have a {OVERRIDE_PATH_KEY} key"))?;
+
+        overrides.push(FlagOverride {
+            path,
+            flags: read_string_list_key(override_table, OVERRIDE_FLAGS_KEY)?.unwrap_or_default(),
+            remove_flags: read_string_list_key(override_table, OVERRIDE_REMOVE_FLAGS_KEY)?
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(overrides)
+}
+
 fn to_owned_string_vec(in_list: &[&str]) -> Vec<String> {
     let mut out_list = Vec::new();
 
@@ -123,63 +580,449 @@ fn to_owned_string_vec(in_list: &[&str]) -> Vec<String> {
 
 fn default_configuration() -> Config {
     Config {
+        target: Target::native(),
+        origins: HashMap::new(),
+        cross_compiling: false,
+        sysroot: None,
         compiler: DEFAULT_COMPILER.to_owned(),
+        c_compiler: DEFAULT_C_COMPILER.to_owned(),
         debugger: DEFAULT_DEBUGGER.to_owned(),
+        debugger_kind: DebuggerKind::detect(DEFAULT_DEBUGGER),
         linter: DEFAULT_LINTER.to_owned(),
+        formatter: DEFAULT_FORMATTER.to_owned(),
         flags: to_owned_string_vec(DEFAULT_FLAGS),
+        c_flags: to_owned_string_vec(DEFAULT_C_FLAGS),
+        cxx_flags: to_owned_string_vec(DEFAULT_CXX_FLAGS),
+        asm_flags: to_owned_string_vec(DEFAULT_ASM_FLAGS),
+        c_standard: None,
+        cxx_standard: None,
+        warnings: None,
+        warnings_as_errors: false,
+        defines: Vec::new(),
+        include_dirs: Vec::new(),
         debug_flags: to_owned_string_vec(DEFAULT_DEBUG_FLAGS),
         release_flags: to_owned_string_vec(DEFAULT_RELEASE_FLAGS),
         linker_flags: to_owned_string_vec(DEFAULT_LINKER_FLAGS),
-        linter_checks: DEFAULT_LINTER_CHECKS.to_owned(),
+        flag_overrides: Vec::new(),
+        lto: false,
+        sanitizers: Vec::new(),
+        cxx_toolchain: OnceCell::new(),
+        c_toolchain: OnceCell::new(),
+        c_extensions: to_owned_string_vec(DEFAULT_C_EXTENSIONS),
+        cxx_extensions: to_owned_string_vec(DEFAULT_CXX_EXTENSIONS),
+        asm_extensions: to_owned_string_vec(DEFAULT_ASM_EXTENSIONS),
+        header_extensions: to_owned_string_vec(DEFAULT_HEADER_EXTENSIONS),
+        exclude: Vec::new(),
+        respect_gitignore: false,
+        linter_checks: TidyConfig {
+            checks: to_owned_string_vec(DEFAULT_LINTER_CHECKS),
+            ..TidyConfig::default()
+        },
+        linter_overrides: Vec::new(),
     }
 }
 
-fn read_configuration(config_path: &str) -> Result<Config, String> {
+fn read_project_file(config_path: &str) -> Result<Table, String> {
     match std::fs::read_to_string(format!("{config_path}{SEPARATOR}{CONFIG_FILE}")) {
-        Ok(toml_str) => match toml_str.parse::<Table>() {
-            Ok(toml) => {
-                let compiler = read_string_key(&toml, COMPILER_KEY)?
-                    .unwrap_or_else(|| DEFAULT_COMPILER.to_owned());
-                let debugger = read_string_key(&toml, DEBUGGER_KEY)?
-                    .unwrap_or_else(|| DEFAULT_DEBUGGER.to_owned());
-                let linter = read_string_key(&toml, LINTER_KEY)?
-                    .unwrap_or_else(|| DEFAULT_LINTER.to_owned());
-
-                let flags = read_string_list_key(&toml, FLAGS_KEY)?
-                    .unwrap_or_else(|| to_owned_string_vec(DEFAULT_FLAGS));
-                let debug_flags = read_string_list_key(&toml, DEBUG_FLAGS_KEY)?
-                    .unwrap_or_else(|| to_owned_string_vec(DEFAULT_DEBUG_FLAGS));
-                let release_flags = read_string_list_key(&toml, RELEASE_FLAGS_KEY)?
-                    .unwrap_or_else(|| to_owned_string_vec(DEFAULT_RELEASE_FLAGS));
-
-                let linker_flags = read_string_list_key(&toml, LINKER_FLAGS_KEY)?
-                    .unwrap_or_else(|| to_owned_string_vec(DEFAULT_LINKER_FLAGS));
-
-                let linter_checks = read_string_key(&toml, LINTER_CHECKS_KEY)?
-                    .unwrap_or_else(|| DEFAULT_LINTER_CHECKS.to_owned());
-
-                Ok(Config {
-                    compiler,
-                    debugger,
-                    linter,
-                    flags,
-                    debug_flags,
-                    release_flags,
-                    linker_flags,
-                    linter_checks,
-                })
-            }
-
-            Err(toml_parse_error) => Err(format!(
+        Ok(toml_str) => toml_str.parse::<Table>().map_err(|toml_parse_error| {
+            format!(
                 "Can't parse {CONFIG_FILE} file ! Does it contain valid toml ? : {toml_parse_error}"
-            )),
-        },
+            )
+        }),
         Err(toml_read_error) => Err(format!(
             "Can't read {CONFIG_FILE} file ! Are you in a project folder ? : {toml_read_error}"
         )),
     }
 }
 
+/// Location of the per-user configuration : `$XDG_CONFIG_HOME/embargo/config.toml`, or
+/// `~/.config/embargo/config.toml` when `XDG_CONFIG_HOME` isn't set
+fn user_config_path() -> Option<PathBuf> {
+    let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| non_empty("APPDATA").map(PathBuf::from))
+        .map(|dir| dir.join(USER_CONFIG_DIR).join(USER_CONFIG_FILE))
+}
+
+/// Reads the per-user configuration, which is optional
+fn read_user_file() -> Result<Option<ConfigFile>, String> {
+    let Some(path) = user_config_path() else {
+        return Ok(None);
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(toml_str) => toml_str
+            .parse::<Table>()
+            .map(|toml| Some(ConfigFile::new(toml, path.display().to_string())))
+            .map_err(|toml_parse_error| {
+                format!(
+                    "Can't parse user configuration {} : {toml_parse_error}",
+                    path.display()
+                )
+            }),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(toml_read_error) => Err(format!(
+            "Can't read user configuration {} : {toml_read_error}",
+            path.display()
+        )),
+    }
+}
+
+/// Configuration file, its target sections are merged once the target is known
+struct ConfigFile {
+    toml: Table,
+    target_sections: Option<Value>,
+
+    /// Shown as the source of the keys it sets
+    origin: String,
+}
+
+impl ConfigFile {
+    fn new(mut toml: Table, origin: String) -> Self {
+        let target_sections = toml.remove(TARGET_KEY);
+
+        Self {
+            toml,
+            target_sections,
+            origin,
+        }
+    }
+}
+
+/// Reads the `--config` options, toml fragments like `compiler = "clang++-17"`
+fn read_config_options(options: &[String]) -> Result<Vec<ConfigFile>, String> {
+    options
+        .iter()
+        .map(|option| {
+            option
+                .parse::<Table>()
+                .map(|toml| ConfigFile::new(toml, CONFIG_OPTION_ORIGIN.to_owned()))
+                .map_err(|toml_parse_error| {
+                    format!(
+                        "Can't parse --config {option} ! Is it a toml key = value ? : \
+                         {toml_parse_error}"
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Reads the configuration, each layer replaces the keys set by the previous ones :
+/// the user configuration and its matching target sections, Embargo.toml and its matching
+/// target sections, the environment variables, then the `--config` options
+fn read_configuration(
+    config_path: &str,
+    target: Option<&str>,
+    config_options: &[String],
+) -> Result<Config, String> {
+    let mut files = Vec::new();
+
+    if let Some(user_file) = read_user_file()? {
+        files.push(user_file);
+    }
+
+    files.push(ConfigFile::new(
+        read_project_file(config_path)?,
+        CONFIG_FILE.to_owned(),
+    ));
+
+    let environment = environment_values();
+    let options = read_config_options(config_options)?;
+
+    let cross_compiling = target.is_some();
+
+    let target = if let Some(triple) = target {
+        Target::from_triple(triple)
+    } else if files
+        .iter()
+        .chain(&options)
+        .any(|file| file.target_sections.is_some())
+    {
+        // The target sections can't change the compiler used to find the target
+        let mut toml = Table::new();
+        for file in &files {
+            toml.extend(file.toml.clone());
+        }
+        for (key, value, _) in &environment {
+            toml.insert(key.clone(), value.clone());
+        }
+        for option in &options {
+            toml.extend(option.toml.clone());
+        }
+
+        let compiler =
+            read_string_key(&toml, COMPILER_KEY)?.unwrap_or_else(|| DEFAULT_COMPILER.to_owned());
+
+        Target::host(&compiler)
+    } else {
+        Target::native()
+    };
+
+    let mut layers = ConfigLayers::default();
+
+    for file in files {
+        layers.merge_file(file, &target)?;
+    }
+
+    for (key, value, origin) in environment {
+        layers.set(key, value, &origin);
+    }
+
+    for option in options {
+        layers.merge_file(option, &target)?;
+    }
+
+    let mut config = config_from_toml(&layers.toml, target)?;
+    config.cross_compiling = cross_compiling;
+    config.origins = layers.origins;
+
+    Ok(config)
+}
+
+/// Configuration keys merged from several sources, each key keeps the value of the last source
+/// setting it
+#[derive(Default)]
+struct ConfigLayers {
+    toml: Table,
+
+    /// Source of the value of each key
+    origins: HashMap<String, String>,
+}
+
+impl ConfigLayers {
+    fn set(&mut self, key: String, value: Value, origin: &str) {
+        self.origins.insert(key.clone(), origin.to_owned());
+        self.toml.insert(key, value);
+    }
+
+    fn merge(&mut self, toml: Table, origin: &str) {
+        for (key, value) in toml {
+            self.set(key, value, origin);
+        }
+    }
+
+    /// Merges the keys of the file, then the keys of its sections matching the target
+    fn merge_file(&mut self, file: ConfigFile, target: &Target) -> Result<(), String> {
+        self.merge(file.toml, &file.origin);
+
+        if let Some(target_sections) = file.target_sections {
+            merge_target_sections(self, target_sections, target, &file.origin)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the keys with the keys of the `[target.<triple>]` and `[target.'cfg(...)']`
+/// sections matching the target, in the order they are written
+fn merge_target_sections(
+    layers: &mut ConfigLayers,
+    sections: Value,
+    target: &Target,
+    origin: &str,
+) -> Result<(), String> {
+    let error = || format!("{TARGET_KEY} value must be a table of tables");
+
+    let Value::Table(sections) = sections else {
+        return Err(error());
+    };
+
+    for (name, section) in sections {
+        let Value::Table(section) = section else {
+            return Err(error());
+        };
+
+        if target
+            .matches(&name)
+            .map_err(|message| format!("Invalid {TARGET_KEY}.{name} section : {message}"))?
+        {
+            layers.merge(section, &format!("{origin} {TARGET_KEY}.{name}"));
+        }
+    }
+
+    Ok(())
+}
+
+fn split_list(value: &str) -> Value {
+    Value::Array(value.split_whitespace().map(Value::from).collect())
+}
+
+fn environment_values() -> Vec<(String, Value, String)> {
+    environment_overrides(&std::env::vars().collect())
+}
+
+/// Keys set by the environment variables, with the name of the variable. The conventional
+/// variables come first, so that `EMBARGO_*` variables take precedence over them. Empty
+/// variables are ignored, like build tools usually do
+fn environment_overrides(variables: &HashMap<String, String>) -> Vec<(String, Value, String)> {
+    let mut values = Vec::new();
+
+    for (variable, key, is_list) in ENVIRONMENT_VARIABLES {
+        if let Some(value) = variables
+            .get(*variable)
+            .filter(|value| !value.trim().is_empty())
+        {
+            let value = if *is_list {
+                split_list(value)
+            } else {
+                Value::String(value.clone())
+            };
+
+            values.push((
+                (*key).to_owned(),
+                value,
+                format!("{variable} environment variable"),
+            ));
+        }
+    }
+
+    let mut embargo_variables = variables
+        .iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .filter_map(|(variable, value)| {
+            let key = variable
+                .strip_prefix(ENVIRONMENT_PREFIX)?
+                .to_lowercase()
+                .replace('_', "-");
+
+            Some((variable, key, value))
+        })
+        .collect::<Vec<_>>();
+    embargo_variables.sort();
+
+    for (variable, key, value) in embargo_variables {
+        let value = environment_value(&key, value);
+        values.push((key, value, format!("{variable} environment variable")));
+    }
+
+    values
+}
+
+/// Reads the value of an `EMBARGO_*` variable as a toml value. Values that are not valid toml,
+/// like `EMBARGO_COMPILER=clang++`, are strings. List settings also accept the whitespace
+/// separated form of `CFLAGS` (`EMBARGO_C_FLAGS="-O1 -g"`, `EMBARGO_FLAGS=-1`)
+fn environment_value(key: &str, value: &str) -> Value {
+    let toml_value = format!("value = {value}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"));
+
+    let is_list = ENVIRONMENT_LIST_KEYS.contains(&key);
+
+    match toml_value {
+        Some(Value::Array(array)) => Value::Array(array),
+        Some(Value::String(string)) if is_list => split_list(&string),
+        Some(toml_value) if !is_list => toml_value,
+        _ if is_list => split_list(value),
+        _ => Value::String(value.to_owned()),
+    }
+}
+
+fn config_from_toml(toml: &Table, target: Target) -> Result<Config, String> {
+    let compiler =
+        read_string_key(toml, COMPILER_KEY)?.unwrap_or_else(|| DEFAULT_COMPILER.to_owned());
+    let c_compiler =
+        read_string_key(toml, C_COMPILER_KEY)?.unwrap_or_else(|| DEFAULT_C_COMPILER.to_owned());
+    let debugger =
+        read_string_key(toml, DEBUGGER_KEY)?.unwrap_or_else(|| DEFAULT_DEBUGGER.to_owned());
+    let debugger_kind = read_debugger_kind_key(toml, &debugger)?;
+    let linter = read_string_key(toml, LINTER_KEY)?.unwrap_or_else(|| DEFAULT_LINTER.to_owned());
+    let formatter =
+        read_string_key(toml, FORMATTER_KEY)?.unwrap_or_else(|| DEFAULT_FORMATTER.to_owned());
+
+    let flags = read_string_list_key_or(toml, FLAGS_KEY, DEFAULT_FLAGS)?;
+    let c_flags = read_string_list_key_or(toml, C_FLAGS_KEY, DEFAULT_C_FLAGS)?;
+    let cxx_flags = read_string_list_key_or(toml, CXX_FLAGS_KEY, DEFAULT_CXX_FLAGS)?;
+    let asm_flags = read_string_list_key_or(toml, ASM_FLAGS_KEY, DEFAULT_ASM_FLAGS)?;
+
+    // std sets the standard of the language it names, unless the language specific key is present
+    let standard = read_string_key(toml, STD_KEY)?;
+    let c_standard = read_string_key(toml, C_STANDARD_KEY)?.or_else(|| {
+        standard
+            .clone()
+            .filter(|standard| Language::of_standard(standard) == Language::C)
+    });
+    let cxx_standard = read_string_key(toml, CXX_STANDARD_KEY)?.or_else(|| {
+        standard
+            .clone()
+            .filter(|standard| Language::of_standard(standard) == Language::Cxx)
+    });
+
+    let warnings = read_warnings_key(toml)?;
+    let warnings_as_errors = read_bool_key(toml, WARNINGS_AS_ERRORS_KEY)?.unwrap_or(false);
+    let defines = read_defines_key(toml)?;
+    let include_dirs = read_string_list_key(toml, INCLUDE_DIRS_KEY)?.unwrap_or_default();
+    let debug_flags = read_string_list_key_or(toml, DEBUG_FLAGS_KEY, DEFAULT_DEBUG_FLAGS)?;
+    let release_flags = read_string_list_key_or(toml, RELEASE_FLAGS_KEY, DEFAULT_RELEASE_FLAGS)?;
+
+    let linker_flags = read_string_list_key_or(toml, LINKER_FLAGS_KEY, DEFAULT_LINKER_FLAGS)?;
+    let flag_overrides = read_flag_overrides_key(toml)?;
+    let lto = read_bool_key(toml, LTO_KEY)?.unwrap_or(false);
+    let sanitizers = read_string_list_key(toml, SANITIZERS_KEY)?.unwrap_or_default();
+
+    let c_extensions = read_string_list_key_or(toml, C_EXTENSIONS_KEY, DEFAULT_C_EXTENSIONS)?;
+    let cxx_extensions = read_string_list_key_or(toml, CXX_EXTENSIONS_KEY, DEFAULT_CXX_EXTENSIONS)?;
+    let asm_extensions = read_string_list_key_or(toml, ASM_EXTENSIONS_KEY, DEFAULT_ASM_EXTENSIONS)?;
+    let header_extensions =
+        read_string_list_key_or(toml, HEADER_EXTENSIONS_KEY, DEFAULT_HEADER_EXTENSIONS)?;
+    let exclude = read_string_list_key(toml, EXCLUDE_KEY)?.unwrap_or_default();
+    let respect_gitignore = read_bool_key(toml, RESPECT_GITIGNORE_KEY)?.unwrap_or(false);
+
+    let linter_checks = TidyConfig {
+        checks: read_pattern_list_key(toml, LINTER_CHECKS_KEY)?
+            .unwrap_or_else(|| to_owned_string_vec(DEFAULT_LINTER_CHECKS)),
+        warnings_as_errors: read_pattern_list_key(toml, LINTER_WARNINGS_AS_ERRORS_KEY)?
+            .unwrap_or_default(),
+        check_options: read_check_options_key(toml, LINTER_CHECK_OPTIONS_KEY)?.unwrap_or_default(),
+    };
+
+    let linter_overrides = read_linter_overrides_key(toml)?;
+
+    let sysroot = read_string_key(toml, SYSROOT_KEY)?;
+
+    Ok(Config {
+        target,
+        origins: HashMap::new(),
+        cross_compiling: false,
+        sysroot,
+        compiler,
+        c_compiler,
+        debugger,
+        debugger_kind,
+        linter,
+        formatter,
+        flags,
+        c_flags,
+        cxx_flags,
+        asm_flags,
+        c_standard,
+        cxx_standard,
+        warnings,
+        warnings_as_errors,
+        defines,
+        include_dirs,
+        debug_flags,
+        release_flags,
+        linker_flags,
+        flag_overrides,
+        lto,
+        sanitizers,
+        cxx_toolchain: OnceCell::new(),
+        c_toolchain: OnceCell::new(),
+        c_extensions,
+        cxx_extensions,
+        asm_extensions,
+        header_extensions,
+        exclude,
+        respect_gitignore,
+        linter_checks,
+        linter_overrides,
+    })
+}
+
 fn find_file(dir: &str, extensions: &[&str]) -> Result<Vec<String>, String> {
     let mut files = Vec::new();
 
@@ -209,29 +1052,104 @@ fn find_file(dir: &str, extensions: &[&str]) -> Result<Vec<String>, String> {
     Ok(files)
 }
 
-fn find_srcs() -> Result<Vec<String>, String> {
-    find_file(SRC_DIR, &[".cpp", ".c"])
+/// Removes the files matching an `exclude` pattern, and the files ignored by git when
+/// `respect-gitignore` is set
+fn remove_excluded(config: &Config, files: &mut Vec<String>) -> Result<(), String> {
+    files.retain(|file| !config.is_excluded(file));
+
+    if config.respect_gitignore {
+        let ignored = git::ignored_files(&[SRC_DIR, INCLUDE_DIR])?
+            .iter()
+            .map(|file| normalize_path(file))
+            .collect::<Vec<_>>();
+
+        files.retain(|file| !ignored.contains(&normalize_path(file)));
+    }
+
+    Ok(())
+}
+
+fn find_srcs(config: &Config) -> Result<Vec<String>, String> {
+    let mut files = find_file(SRC_DIR, &config.source_extensions())?;
+
+    remove_excluded(config, &mut files)?;
+
+    Ok(files)
+}
+
+/// C and C++ sources, assembly files can't be checked or linted
+fn find_c_cxx_srcs(config: &Config) -> Result<Vec<String>, String> {
+    Ok(find_srcs(config)?
+        .into_iter()
+        .filter(|source| config.language_of(source) != Language::Asm)
+        .collect())
+}
+
+fn find_code(config: &Config) -> Result<Vec<String>, String> {
+    let code_extensions = config.code_extensions();
+
+    let mut files = find_file(SRC_DIR, &code_extensions)?;
+
+    if Path::new(INCLUDE_DIR).is_dir() {
+        files.append(&mut find_file(INCLUDE_DIR, &code_extensions)?);
+    }
+
+    remove_excluded(config, &mut files)?;
+
+    Ok(files)
 }
 
-fn find_code() -> Result<Vec<String>, String> {
-    find_file(SRC_DIR, &[".hpp", ".h", ".cpp", ".c"])
+/// Objects of every source of the project, in the same order as `find_srcs`
+fn find_objects(config: &Config, build_subdir: &str) -> Result<Vec<String>, String> {
+    Ok(find_srcs(config)?
+        .iter()
+        .map(|source| object_path(build_subdir, source))
+        .collect())
 }
 
-fn find_objects(build_subdir: &str) -> Result<Vec<String>, String> {
-    find_file(&format!("{BUILD_DIR}{SEPARATOR}{build_subdir}"), &[".o"])
+/// Removes the objects, dependency files and command records left by sources that were deleted
+/// or renamed, so that they are not linked into the app
+fn remove_stale_objects(build_subdir: &str, objects: &[String]) -> Result<(), String> {
+    let subdir = format!("{BUILD_DIR}{SEPARATOR}{build_subdir}");
+
+    if !Path::new(&subdir).is_dir() {
+        return Ok(());
+    }
+
+    let expected = objects
+        .iter()
+        .flat_map(|object| {
+            [
+                normalize_path(object),
+                normalize_path(&dependency_path(object)),
+                normalize_path(&command_record_path(object)),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    for file in find_file(&subdir, &[".o", ".d", ".cmd"])? {
+        if !expected.contains(&normalize_path(&file)) {
+            std::fs::remove_file(&file)
+                .map_err(|error| format!("Can't remove {file} : {error}"))?;
+        }
+    }
+
+    Ok(())
 }
 
-fn compile_object(options: (String, Vec<String>, String, String)) -> Result<bool, String> {
+fn compile_object(options: (String, Vec<String>, String, String, String)) -> Result<bool, String> {
     let compiler = options.0;
     let flags = options.1;
     let input = options.2;
     let output = options.3;
+    let record = options.4;
 
     let mut compile_command = Command::new(compiler);
 
     compile_command.args(flags);
     compile_command.arg("-c");
-    compile_command.arg("-fcolor-diagnostics");
+    compile_command.arg("-MMD");
+    compile_command.arg(format!("-MF{}", dependency_path(&output)));
     compile_command.arg(format!("-o{output}"));
     compile_command.arg(input);
 
@@ -242,6 +1160,42 @@ fn compile_object(options: (String, Vec<String>, String, String)) -> Result<bool
         }
     }
 
+    // A failed compilation leaves no record, so that the source is compiled again
+    let record_path = command_record_path(&output);
+    if let Err(error) = std::fs::remove_file(&record_path) {
+        if error.kind() != io::ErrorKind::NotFound {
+            return Err(format!("Can't remove {record_path} : {error}"));
+        }
+    }
+
+    let succeeded = run_compiler(&mut compile_command)?;
+
+    if succeeded {
+        std::fs::write(&record_path, record)
+            .map_err(|error| format!("Can't write {record_path} : {error}"))?;
+    }
+
+    Ok(succeeded)
+}
+
+/// Only checks the syntax and types of a source file, without writing an object file
+fn check_source(options: (String, Vec<String>, String)) -> Result<bool, String> {
+    let compiler = options.0;
+    let flags = options.1;
+    let input = options.2;
+
+    let mut check_command = Command::new(compiler);
+
+    check_command.args(flags);
+    check_command.arg("-fsyntax-only");
+    check_command.arg(input);
+
+    run_compiler(&mut check_command)
+}
+
+/// Runs the compiler, its output is written at once so that the diagnostics of compilers
+/// running in parallel don't mix
+fn run_compiler(compile_command: &mut Command) -> Result<bool, String> {
     let compile_result = compile_command.output();
 
     match compile_result {
@@ -272,29 +1226,102 @@ fn compile_object(options: (String, Vec<String>, String, String)) -> Result<bool
     }
 }
 
-fn compile_all_objects(compiler: &str, flags: &[&str], build_subdir: &str) -> Result<bool, String> {
-    let source_files = match find_srcs() {
-        Ok(srcs) => srcs,
-        Err(error) => {
-            return Err(error);
-        }
+fn modification_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// An object is up to date if it was compiled with the same command, whatever configuration
+/// file or variable set it, and if it is newer than its source and every header the source
+/// includes (read from the dependency file written by the compiler)
+fn is_up_to_date(source: &str, object: &str, record: &str, preprocessed: bool) -> bool {
+    let Some(object_time) = modification_time(object) else {
+        return false;
     };
 
-    let mut compile_parameters = Vec::new();
+    let Ok(previous_record) = std::fs::read_to_string(command_record_path(object)) else {
+        return false;
+    };
+
+    if previous_record != record {
+        return false;
+    }
+
+    // The compiler writes no dependency file for sources that are not preprocessed, they
+    // include no header
+    let dependency_file = match std::fs::read_to_string(dependency_path(object)) {
+        Ok(dependency_file) => dependency_file,
+        Err(_) if !preprocessed => String::new(),
+        Err(_) => return false,
+    };
+
+    depfile::parse(&dependency_file)
+        .iter()
+        .map(String::as_str)
+        .chain([source])
+        .all(|dependency| modification_time(dependency).is_some_and(|time| time <= object_time))
+}
+
+/// The object keeps the full source file name (`src/util.cpp` is compiled to
+/// `build/debug/src/util.cpp.o`), so that `util.c` and `util.cpp` don't share the same object
+fn object_path(build_subdir: &str, source: &str) -> String {
+    format!("{BUILD_DIR}{SEPARATOR}{build_subdir}{SEPARATOR}{source}.o")
+}
+
+/// Dependency file written by the compiler next to the object (`util.cpp.d`)
+fn dependency_path(object: &str) -> String {
+    Path::new(object)
+        .with_extension("d")
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Compiler and flags an object was compiled with, saved next to the object (`util.cpp.cmd`)
+/// so that changing them from the environment or the command line rebuilds it
+fn command_record_path(object: &str) -> String {
+    Path::new(object)
+        .with_extension("cmd")
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// One argument per line, the color flag isn't recorded as it doesn't change the object
+fn command_record(compiler: &str, flags: &[String]) -> String {
+    let mut record = String::new();
+
+    for argument in std::iter::once(compiler).chain(flags.iter().map(String::as_str)) {
+        record.push_str(argument);
+        record.push('\n');
+    }
+
+    record
+}
+
+fn compile_all_objects(
+    config: &Config,
+    release: bool,
+    build_subdir: &str,
+    source_files: Vec<String>,
+) -> Result<bool, String> {
+    let mut compile_parameters = Vec::new();
 
     for source_file in source_files {
-        let compiler_s = compiler.to_owned();
-        let mut flags_s = Vec::new();
-        for &flag in flags {
-            flags_s.push(flag.to_owned());
-        }
+        let (compiler_s, mut flags_s) = source_compile_flags(config, release, &source_file);
+        let record = command_record(&compiler_s, &flags_s);
+        flags_s.extend(color_flag(config, &source_file));
         let input_s = source_file;
-        let output_s = format!(
-            "{BUILD_DIR}{SEPARATOR}{build_subdir}{SEPARATOR}{}",
-            Path::new(&input_s).with_extension("o").to_string_lossy()
-        );
+        let output_s = object_path(build_subdir, &input_s);
 
-        compile_parameters.push((compiler_s, flags_s, input_s, output_s));
+        // Lowercase .s assembly is assembled as is, without the preprocessor
+        let preprocessed = !(config.language_of(&input_s) == Language::Asm
+            && Path::new(&input_s)
+                .extension()
+                .is_some_and(|extension| extension == "s"));
+
+        if !is_up_to_date(&input_s, &output_s, &record, preprocessed) {
+            compile_parameters.push((compiler_s, flags_s, input_s, output_s, record));
+        }
     }
 
     let results = parallel_run(compile_parameters, compile_object);
@@ -313,23 +1340,26 @@ fn compile_all_objects(compiler: &str, flags: &[&str], build_subdir: &str) -> Re
     Ok(true)
 }
 
-fn link_program(compiler: &str, flags: &[&str], build_subdir: &str) -> Result<bool, String> {
-    let obj_files = match find_objects(build_subdir) {
-        Ok(objects) => objects,
-        Err(error) => {
-            return Err(error);
-        }
-    };
-
+fn link_program(
+    config: &Config,
+    flags: &[&str],
+    obj_files: &[String],
+    build_subdir: &str,
+) -> Result<bool, String> {
     let subdir = format!("{BUILD_DIR}{SEPARATOR}{build_subdir}");
     if let Err(error) = create_dir_all(&subdir) {
         return Err(format!("Can't create {subdir} directory : {error}"));
     }
 
-    let mut link_command = Command::new(compiler);
+    let mut link_command = Command::new(&config.compiler);
 
     link_command.args(flags);
-    link_command.arg(format!("-o{subdir}{SEPARATOR}app{EXE_EXTENSION}"));
+    link_command.args(
+        config
+            .toolchain(Language::Cxx)
+            .color_flag(color::enabled(Stream::Stderr)),
+    );
+    link_command.arg(format!("-o{}", app_path(config, build_subdir)));
     link_command.args(obj_files);
 
     let link_result = link_command.status();
@@ -347,260 +1377,963 @@ fn link_program(compiler: &str, flags: &[&str], build_subdir: &str) -> Result<bo
     }
 }
 
-fn build(config: &Config, release: bool) -> Result<bool, String> {
-    let mut flags = Vec::<&str>::new();
+/// Build directory of a profile, inside the directory of the target when cross compiling
+fn build_subdir(config: &Config, release: bool) -> String {
+    let profile = if release {
+        RELEASE_BUILD_SUBDIR
+    } else {
+        DEBUG_BUILD_SUBDIR
+    };
+
+    if config.cross_compiling {
+        format!("{}{SEPARATOR}{profile}", config.target.triple)
+    } else {
+        profile.to_owned()
+    }
+}
+
+fn app_path(config: &Config, build_subdir: &str) -> String {
+    format!(
+        "{BUILD_DIR}{SEPARATOR}{build_subdir}{SEPARATOR}app{}",
+        config.target.exe_extension()
+    )
+}
 
-    for f in &config.flags {
-        flags.push(f);
+/// Flags shared by every source, before the profile and language specific flags
+fn common_flags(config: &Config) -> Vec<String> {
+    let mut flags = config.flags.clone();
+
+    if let Some(warnings) = config.warnings {
+        flags.append(&mut to_owned_string_vec(warnings.flags()));
+    }
+
+    if config.warnings_as_errors {
+        flags.push("-Werror".to_owned());
     }
 
+    for (name, value) in &config.defines {
+        match value {
+            Some(value) => flags.push(format!("-D{name}={value}")),
+            None => flags.push(format!("-D{name}")),
+        }
+    }
+
+    flags.push(format!("-I{INCLUDE_DIR}"));
+
+    for include_dir in &config.include_dirs {
+        flags.push(format!("-I{include_dir}"));
+    }
+
+    flags
+}
+
+fn compile_flags(config: &Config, release: bool) -> Vec<String> {
+    let mut flags = common_flags(config);
+
     if release {
         for f in &config.release_flags {
-            flags.push(f);
+            flags.push(f.clone());
         }
     } else {
         for f in &config.debug_flags {
-            flags.push(f);
+            flags.push(f.clone());
         }
     }
 
-    let include_flag = format!("-I{INCLUDE_DIR}");
-    flags.push(&include_flag);
+    flags
+}
 
-    let build_subdir = if release {
-        RELEASE_BUILD_SUBDIR
-    } else {
-        DEBUG_BUILD_SUBDIR
+/// Language specific flags, added after the flags shared by every source
+fn language_flags(config: &Config, language: Language) -> Vec<String> {
+    let (language_flags, standard) = match language {
+        Language::C => (&config.c_flags, &config.c_standard),
+        Language::Cxx => (&config.cxx_flags, &config.cxx_standard),
+        Language::Asm => (&config.asm_flags, &None),
     };
 
-    if compile_all_objects(&config.compiler, &flags, build_subdir)? {
-        for f in &config.linker_flags {
-            flags.push(f);
-        }
+    let mut flags = Vec::new();
 
-        link_program(&config.compiler, &flags, build_subdir)
-    } else {
-        Ok(false)
+    if let Some(standard) = standard {
+        flags.push(format!("-std={standard}"));
     }
+
+    for f in language_flags {
+        flags.push(f.clone());
+    }
+
+    flags
 }
 
-fn lint(linter: &str, checks: &str, compile_flags: &[&str]) {
-    let code_files = match find_code() {
-        Ok(files) => files,
-        Err(error) => {
-            eprintln!("{error}");
-            return;
-        }
+/// Flags of the generic options, translated for a toolchain, they are used both to compile and
+/// to link
+fn toolchain_flags(config: &Config, toolchain: Toolchain, release: bool) -> Vec<String> {
+    let mut flags = Vec::new();
+
+    if config.cross_compiling {
+        flags.extend(toolchain.target_flag(&config.target.triple));
+    }
+
+    if let Some(sysroot) = &config.sysroot {
+        flags.push(format!("--sysroot={sysroot}"));
+    }
+
+    if release && config.lto {
+        flags.push(toolchain.lto_flag().to_owned());
+    }
+
+    if !release && !config.sanitizers.is_empty() {
+        flags.push(format!("-fsanitize={}", config.sanitizers.join(",")));
+        // Gives readable stack traces in the sanitizer reports
+        flags.push("-fno-omit-frame-pointer".to_owned());
+    }
+
+    flags
+}
+
+/// gcc compilers only produce code for the target they were built for, building with a
+/// compiler of another target would give an app for the wrong platform
+fn check_cross_compiler(config: &Config, language: Language) -> Result<(), Error> {
+    if !config.cross_compiling || config.toolchain(language).family != Family::Gcc {
+        return Ok(());
+    }
+
+    let (key, compiler, driver) = match language {
+        Language::C | Language::Asm => (C_COMPILER_KEY, &config.c_compiler, "gcc"),
+        Language::Cxx => (COMPILER_KEY, &config.compiler, "g++"),
     };
 
-    let mut lint_command = Command::new(linter);
+    // Vendors can differ, x86_64-pc-linux-gnu and x86_64-linux-gnu are the same target
+    let compiler_target = Target::host(compiler);
+    let target = &config.target;
 
-    lint_command.args(code_files);
-    lint_command.arg(format!("-checks={checks}"));
-    lint_command.arg("--");
-    lint_command.args(compile_flags);
+    if compiler_target.arch == target.arch
+        && compiler_target.os == target.os
+        && compiler_target.env == target.env
+    {
+        Ok(())
+    } else {
+        let triple = &target.triple;
 
-    let lint_result = lint_command.status();
+        Err(Error::Config(format!(
+            "{compiler} builds for {}, not for {triple} ! Use the gcc compiler of the target \
+             ({key} = \"{triple}-{driver}\") or clang",
+            compiler_target.triple
+        )))
+    }
+}
 
-    match lint_result {
-        Ok(exit_status) => {
-            if exit_status.success() {
-                println!("Finished");
-            } else {
-                println!("Finished, with errors");
-            }
+/// Languages of the given sources, each one once
+fn source_languages(config: &Config, sources: &[String]) -> Vec<Language> {
+    let mut languages = Vec::new();
+
+    for source in sources {
+        let language = config.language_of(source);
+
+        if !languages.contains(&language) {
+            languages.push(language);
         }
+    }
 
-        Err(error) => {
-            eprintln!("Can't run linter : {error}");
+    languages
+}
+
+/// Flag of the `--color` choice for the diagnostics of a source
+fn color_flag(config: &Config, source: &str) -> Option<String> {
+    config
+        .toolchain(config.language_of(source))
+        .color_flag(color::enabled(Stream::Stderr))
+        .map(str::to_owned)
+}
+
+/// Compiler and flags used to compile a source file, depending on its language
+fn source_compile_flags(config: &Config, release: bool, source: &str) -> (String, Vec<String>) {
+    let language = config.language_of(source);
+
+    let compiler = match language {
+        Language::C | Language::Asm => config.c_compiler.clone(),
+        Language::Cxx => config.compiler.clone(),
+    };
+
+    let mut flags = compile_flags(config, release);
+    flags.append(&mut language_flags(config, language));
+    flags.append(&mut toolchain_flags(
+        config,
+        config.toolchain(language),
+        release,
+    ));
+    apply_flag_overrides(config, source, &mut flags);
+
+    (compiler, flags)
+}
+
+/// Applies the overrides matching the source file, in the order they are written
+fn apply_flag_overrides(config: &Config, source: &str, flags: &mut Vec<String>) {
+    for flag_override in &config.flag_overrides {
+        if glob::matches(&flag_override.path, source) {
+            flags.retain(|flag| !flag_override.remove_flags.contains(flag));
+            flags.extend(flag_override.flags.iter().cloned());
         }
     }
 }
 
-fn build_command(config: &Config) {
-    match build(config, false) {
-        Ok(successful) => {
-            if successful {
-                println!("Finished");
-            } else {
-                println!("Finished, with errors");
-            }
+fn build(config: &Config, release: bool) -> Result<(), Error> {
+    let mut compile_flags = compile_flags(config, release);
+    compile_flags.append(&mut toolchain_flags(
+        config,
+        config.toolchain(Language::Cxx),
+        release,
+    ));
+    let mut flags = compile_flags.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let build_subdir = &build_subdir(config, release);
+
+    let source_files = find_srcs(config).map_err(Error::Build)?;
+
+    // The app is linked with the C++ compiler
+    check_cross_compiler(config, Language::Cxx)?;
+    for language in source_languages(config, &source_files) {
+        check_cross_compiler(config, language)?;
+    }
+
+    if !compile_all_objects(config, release, build_subdir, source_files).map_err(Error::Build)? {
+        return Err(Error::Compile);
+    }
+
+    for f in &config.linker_flags {
+        flags.push(f);
+    }
+
+    let objects = find_objects(config, build_subdir).map_err(Error::Build)?;
+    remove_stale_objects(build_subdir, &objects).map_err(Error::Build)?;
+
+    if link_program(config, &flags, &objects, build_subdir).map_err(Error::Build)? {
+        Ok(())
+    } else {
+        Err(Error::Link)
+    }
+}
+
+/// Formats a file in place, or only checks that it is already formatted, returns false if the
+/// file is not formatted and an error if the formatter failed
+fn format_file(options: (String, String, bool)) -> Result<bool, String> {
+    let formatter = options.0;
+    let file = options.1;
+    let check = options.2;
+
+    let mut format_command = Command::new(formatter);
+
+    if check {
+        // Comparing the formatted output with the file works with every clang-format version
+        format_command.arg(&file);
+
+        let format_output = format_command
+            .output()
+            .map_err(|error| format!("Can't run formatter : {error}"))?;
+
+        if !format_output.status.success() {
+            return Err(format!(
+                "Can't format {file} : {}",
+                String::from_utf8_lossy(&format_output.stderr).trim()
+            ));
         }
-        Err(err_msg) => {
-            eprintln!("Build error : {err_msg}");
+
+        let content =
+            std::fs::read(&file).map_err(|error| format!("Can't read {file} : {error}"))?;
+
+        if content == format_output.stdout {
+            Ok(true)
+        } else {
+            println!("{file} is not formatted");
+            Ok(false)
+        }
+    } else {
+        format_command.arg("-i");
+        format_command.arg(&file);
+
+        let format_output = format_command
+            .output()
+            .map_err(|error| format!("Can't run formatter : {error}"))?;
+
+        if format_output.status.success() {
+            Ok(true)
+        } else {
+            Err(format!(
+                "Can't format {file} : {}",
+                String::from_utf8_lossy(&format_output.stderr).trim()
+            ))
         }
     }
 }
 
-fn release_build_command(config: &Config) {
-    match build(config, true) {
-        Ok(successful) => {
-            if successful {
-                println!("Finished");
-            } else {
-                println!("Finished, with errors");
-            }
+fn fmt_command(config: &Config, check: bool) -> Result<(), Error> {
+    let files = find_code(config).map_err(Error::Io)?;
+
+    let mut format_parameters = Vec::new();
+
+    for file in files {
+        format_parameters.push((config.formatter.clone(), file, check));
+    }
+
+    let results = parallel_run(format_parameters, format_file);
+
+    let mut unformatted_count = 0;
+
+    for result in results {
+        if !result.map_err(Error::Io)? {
+            unformatted_count += 1;
         }
-        Err(err_msg) => {
-            eprintln!("Build error : {err_msg}");
+    }
+
+    if unformatted_count == 0 {
+        println!("{}", color::success("Finished"));
+        Ok(())
+    } else {
+        Err(Error::Format(unformatted_count))
+    }
+}
+
+fn check_command(config: &Config) -> Result<(), Error> {
+    let source_files = find_c_cxx_srcs(config).map_err(Error::Build)?;
+
+    for language in source_languages(config, &source_files) {
+        check_cross_compiler(config, language)?;
+    }
+
+    let mut check_parameters = Vec::new();
+
+    for source_file in source_files {
+        let (compiler, mut flags) = source_compile_flags(config, false, &source_file);
+        flags.extend(color_flag(config, &source_file));
+        check_parameters.push((compiler, flags, source_file));
+    }
+
+    for result in parallel_run(check_parameters, check_source) {
+        if !result.map_err(Error::Build)? {
+            return Err(Error::Compile);
         }
     }
+
+    println!("{}", color::success("Finished"));
+    Ok(())
+}
+
+fn build_command(config: &Config) -> Result<(), Error> {
+    build(config, false)?;
+    println!("{}", color::success("Finished"));
+    Ok(())
+}
+
+fn release_build_command(config: &Config) -> Result<(), Error> {
+    build(config, true)?;
+    println!("{}", color::success("Finished"));
+    Ok(())
 }
 
-fn run_command(config: &Config) {
-    match build(config, false) {
-        Ok(successful) => {
-            if successful {
-                let mut run_command = Command::new(&config.debugger);
-                run_command.arg("--source-quietly");
-                run_command.arg("-o");
-                run_command.arg("run");
-                run_command.arg("-o");
-                run_command.arg("exit");
-                run_command.arg(format!(
-                    "{BUILD_DIR}{SEPARATOR}{DEBUG_BUILD_SUBDIR}{SEPARATOR}app{EXE_EXTENSION}"
-                ));
+fn watch_command(
+    mut config: Config,
+    command: WatchedCommand,
+    config_options: &[String],
+) -> Result<(), Error> {
+    let mut watcher =
+        ChangeWatcher::new(&[SRC_DIR, INCLUDE_DIR, TESTS_DIR, CONFIG_FILE]).map_err(Error::Io)?;
 
-                if let Err(error) = run_command.status() {
-                    println!("Can't run your app in debugger : {error}");
+    if let Some(user_config) = user_config_path() {
+        watcher.watch_file(&user_config).map_err(Error::Io)?;
+    }
+
+    let mut app: Option<Child> = None;
+
+    loop {
+        let result = match command {
+            WatchedCommand::Check => check_command(&config),
+            WatchedCommand::Build => build_command(&config),
+            WatchedCommand::Run => build(&config, false).and_then(|()| {
+                let mut run_command = config.debugger_kind.run_command(
+                    &config.debugger,
+                    &app_path(&config, &build_subdir(&config, false)),
+                );
+
+                match run_command.spawn() {
+                    Ok(child) => {
+                        app = Some(child);
+                        Ok(())
+                    }
+                    Err(error) => Err(Error::Io(format!(
+                        "Can't run your app in debugger : {error}"
+                    ))),
                 }
-            } else {
-                println!("Build failed");
-            }
+            }),
+            WatchedCommand::Lint => lint_command(&config, &LintArgs::default()),
+        };
+
+        if let Err(error) = result {
+            eprintln!("{}", color::error(&error.to_string()));
         }
-        Err(err_msg) => {
-            eprintln!("Build error : {err_msg}");
+
+        watcher.wait_for_change().map_err(Error::Io)?;
+
+        // The previous instance of the app may still be running
+        if let Some(mut child) = app.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        let target = config.cross_compiling.then(|| config.target.triple.clone());
+
+        match read_configuration(".", target.as_deref(), config_options) {
+            Ok(new_config) => config = new_config,
+            Err(err_msg) => eprintln!("{}", color::error(&err_msg)),
         }
     }
 }
 
-fn release_run_command(config: &Config) {
-    match build(config, true) {
-        Ok(successful) => {
-            if successful {
-                let mut run_command = Command::new(format!(
-                    "{BUILD_DIR}{SEPARATOR}{RELEASE_BUILD_SUBDIR}{SEPARATOR}app{EXE_EXTENSION}"
-                ));
+fn run_command(config: &Config) -> Result<(), Error> {
+    build(config, false)?;
 
-                if let Err(error) = run_command.status() {
-                    println!("Can't run your app : {error}");
-                }
-            } else {
-                println!("Build failed");
+    let mut run_command = config.debugger_kind.run_command(
+        &config.debugger,
+        &app_path(config, &build_subdir(config, false)),
+    );
+
+    match run_command.status() {
+        Ok(exit_status) => Error::check_app_status(exit_status),
+        Err(error) => Err(Error::Io(format!(
+            "Can't run your app in debugger : {error}"
+        ))),
+    }
+}
+
+fn release_run_command(config: &Config) -> Result<(), Error> {
+    build(config, true)?;
+
+    let mut run_command = Command::new(app_path(config, &build_subdir(config, true)));
+
+    match run_command.status() {
+        Ok(exit_status) => Error::check_app_status(exit_status),
+        Err(error) => Err(Error::Io(format!("Can't run your app : {error}"))),
+    }
+}
+
+fn debug_command(config: &Config) -> Result<(), Error> {
+    build(config, false)?;
+
+    let mut run_command = config.debugger_kind.interactive_command(
+        &config.debugger,
+        &app_path(config, &build_subdir(config, false)),
+    );
+
+    match run_command.status() {
+        Ok(exit_status) => Error::check_app_status(exit_status),
+        Err(error) => Err(Error::Io(format!(
+            "Can't run your app in debugger : {error}"
+        ))),
+    }
+}
+
+fn normalize_path(path: &str) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
             }
-        }
-        Err(err_msg) => {
-            eprintln!("Build error : {err_msg}");
+            _ => normalized.push(component),
         }
     }
+
+    normalized
 }
 
-fn debug_command(config: &Config) {
-    match build(config, false) {
-        Ok(successful) => {
-            if successful {
-                let mut run_command = Command::new(&config.debugger);
-                run_command.arg(format!(
-                    "{BUILD_DIR}{SEPARATOR}{DEBUG_BUILD_SUBDIR}{SEPARATOR}app{EXE_EXTENSION}"
-                ));
+/// Changed sources, and sources including a changed header according to the dependency files
+/// of the last debug build
+fn changed_sources(config: &Config, base: Option<&str>) -> Result<Vec<String>, Error> {
+    let changed_files = git::changed_files(base)
+        .map_err(Error::Io)?
+        .iter()
+        .map(|file| normalize_path(file))
+        .collect::<Vec<_>>();
+
+    let mut sources = Vec::new();
+    let mut untracked_dependencies = false;
+
+    for source in find_c_cxx_srcs(config).map_err(Error::Io)? {
+        if changed_files.contains(&normalize_path(&source)) {
+            sources.push(source);
+            continue;
+        }
 
-                if let Err(error) = run_command.status() {
-                    println!("Can't run your app in debugger : {error}");
+        let object = object_path(&build_subdir(config, false), &source);
+
+        match std::fs::read_to_string(dependency_path(&object)) {
+            Ok(dependency_file) => {
+                if depfile::parse(&dependency_file)
+                    .iter()
+                    .any(|dependency| changed_files.contains(&normalize_path(dependency)))
+                {
+                    sources.push(source);
                 }
-            } else {
-                println!("Build failed");
             }
+            Err(_) => untracked_dependencies = true,
         }
-        Err(err_msg) => {
-            eprintln!("Build error : {err_msg}");
-        }
     }
+
+    if untracked_dependencies {
+        eprintln!(
+            "Some sources were never built, \
+             build them to lint them when a header they include changes"
+        );
+    }
+
+    Ok(sources)
 }
 
-fn lint_command(config: &Config) {
-    let mut flags = Vec::<&str>::new();
+/// Sources with the flags the linter compiles them with, clang flags whatever the compiler is
+fn lint_compile_flags(config: &Config, source_files: Vec<String>) -> Vec<(String, Vec<String>)> {
+    source_files
+        .into_iter()
+        .map(|source_file| {
+            let mut flags = common_flags(config);
+            flags.append(&mut language_flags(
+                config,
+                config.language_of(&source_file),
+            ));
+            flags.append(&mut toolchain_flags(config, Toolchain::clang(), false));
+            apply_flag_overrides(config, &source_file, &mut flags);
+
+            (source_file, flags)
+        })
+        .collect()
+}
 
-    for f in &config.flags {
-        flags.push(f);
+fn lint_command(config: &Config, args: &LintArgs) -> Result<(), Error> {
+    if args.fix && !args.allow_dirty && git::has_uncommitted_changes().map_err(Error::Io)? {
+        return Err(Error::Config(
+            "Can't apply lint fixes, the working tree has uncommitted changes. \
+             Commit them first or use --allow-dirty"
+                .to_owned(),
+        ));
     }
 
-    let f = format!("-I{INCLUDE_DIR}");
-    flags.push(&f);
+    let fixes_dir = format!("{BUILD_DIR}{SEPARATOR}{LINT_FIXES_SUBDIR}");
+
+    let source_files = if args.changed {
+        changed_sources(config, args.base.as_deref())?
+    } else {
+        find_c_cxx_srcs(config).map_err(Error::Io)?
+    };
+
+    // Json and sarif documents must be alone on stdout
+    let print_status = |message: &str| match args.format {
+        LintFormat::Text => println!("{message}"),
+        LintFormat::Json | LintFormat::Sarif => eprintln!("{message}"),
+    };
+
+    // The linter reads its checks from the generated .clang-tidy files when there are some,
+    // like editors do
+    let checks_in_files = clang_tidy::write_config_files(
+        &config.linter_checks,
+        &config.linter_overrides,
+        &[BUILD_DIR],
+    )
+    .map_err(Error::Io)?;
+    let checks = (!checks_in_files).then(|| config.linter_checks.checks.join(","));
+
+    // Without linted files there are no fixes, the fixes directory may not exist or hold the
+    // fixes of a previous run
+    let linted = !source_files.is_empty();
+
+    let mut report = if linted {
+        lint::lint(
+            &config.linter,
+            &[SRC_DIR, INCLUDE_DIR],
+            lint_compile_flags(config, source_files),
+            checks.as_deref(),
+            args.fix.then_some(fixes_dir.as_str()),
+            matches!(args.format, LintFormat::Text) && color::enabled(Stream::Stdout),
+        )
+        .map_err(Error::Io)?
+    } else {
+        print_status(if args.changed {
+            "No changed file to lint"
+        } else {
+            "No file to lint"
+        });
+        LintReport::default()
+    };
 
-    lint(&config.linter, &config.linter_checks, &flags);
+    // Excluded headers are still included by the linted sources
+    report
+        .diagnostics
+        .retain(|diagnostic| !config.is_excluded(&diagnostic.file));
+
+    let mut new_findings = false;
+
+    if args.update_baseline {
+        baseline::write(LINT_BASELINE_FILE, &report.diagnostics).map_err(Error::Io)?;
+        print_status(&format!(
+            "{LINT_BASELINE_FILE} updated with {} finding(s)",
+            report.diagnostics.len()
+        ));
+    } else if let Some(known) = baseline::read(LINT_BASELINE_FILE).map_err(Error::Io)? {
+        let (diagnostics, suppressed_count) = baseline::remove_known(report.diagnostics, known);
+        report.diagnostics = diagnostics;
+        new_findings = !report.diagnostics.is_empty();
+
+        print_status(&format!(
+            "{suppressed_count} finding(s) of {LINT_BASELINE_FILE} ignored"
+        ));
+    }
+
+    let mut warning_count = 0;
+    let mut error_count = 0;
+
+    for diagnostic in &report.diagnostics {
+        match diagnostic.severity {
+            Severity::Error => error_count += 1,
+            Severity::Warning => warning_count += 1,
+            Severity::Note => {}
+        }
+    }
+
+    for failure in &report.failures {
+        eprintln!("{failure}");
+    }
+
+    match args.format {
+        LintFormat::Text => {
+            for diagnostic in &report.diagnostics {
+                println!("{}", diagnostic.colored_text);
+            }
+        }
+        LintFormat::Json => println!("{}", lint::to_json(&report.diagnostics)),
+        LintFormat::Sarif => println!("{}", lint::to_sarif(&report.diagnostics, &config.linter)),
+    }
+
+    print_status(&format!(
+        "{warning_count} warning(s) and {error_count} error(s) found"
+    ));
+
+    if args.fix && linted && !lint::apply_fixes(&config.linter, &fixes_dir).map_err(Error::Io)? {
+        return Err(Error::Io("Can't apply lint fixes".to_owned()));
+    }
+
+    if report.failures.is_empty() && !new_findings {
+        print_status(&color::success("Finished"));
+        Ok(())
+    } else {
+        Err(Error::Lint)
+    }
 }
 
-fn init_command() {
+fn init_command() -> Result<(), Error> {
     if std::path::Path::new(CONFIG_FILE).is_file() {
-        eprintln!("Can't init an already existing embargo project");
-        return;
+        return Err(Error::Config(
+            "Can't init an already existing embargo project".to_owned(),
+        ));
     }
 
     if !std::path::Path::new(SRC_DIR).is_dir() {
-        match std::fs::create_dir(SRC_DIR) {
-            Ok(()) => {
-                if let Err(error) =
-                    std::fs::write(format!("{SRC_DIR}{SEPARATOR}main.cpp"), HELLO_WORLD)
-                {
-                    eprintln!("Can't create default main.cpp file : {error}");
-                }
-            }
+        if let Err(error) = std::fs::create_dir(SRC_DIR) {
+            return Err(Error::Io(format!("Can't create {SRC_DIR} dir : {error}")));
+        }
 
-            Err(error) => {
-                eprintln!("Can't create {SRC_DIR} dir : {error}");
-            }
+        if let Err(error) = std::fs::write(format!("{SRC_DIR}{SEPARATOR}main.cpp"), HELLO_WORLD) {
+            return Err(Error::Io(format!(
+                "Can't create default main.cpp file : {error}"
+            )));
         }
     }
 
     if !std::path::Path::new(INCLUDE_DIR).is_dir() {
         if let Err(error) = std::fs::create_dir(INCLUDE_DIR) {
-            eprintln!("Can't create {INCLUDE_DIR} dir : {error}");
+            return Err(Error::Io(format!(
+                "Can't create {INCLUDE_DIR} dir : {error}"
+            )));
         }
     }
 
-    if !std::path::Path::new(CONFIG_FILE).is_file() {
-        if let Err(error) = std::fs::write(CONFIG_FILE, "") {
-            eprintln!("Can't create {CONFIG_FILE} : {error}");
-        } else {
-            clangd_config_command(&default_configuration());
+    if let Err(error) = std::fs::write(CONFIG_FILE, "") {
+        return Err(Error::Io(format!("Can't create {CONFIG_FILE} : {error}")));
+    }
+
+    if !std::path::Path::new(CLANG_FORMAT_FILE).is_file() {
+        if let Err(error) = std::fs::write(CLANG_FORMAT_FILE, DEFAULT_CLANG_FORMAT) {
+            return Err(Error::Io(format!(
+                "Can't create {CLANG_FORMAT_FILE} : {error}"
+            )));
         }
     }
+
+    clangd_config_command(&default_configuration())
+}
+
+/// Label, keys the value is read from and value of a setting shown by `embargo config`
+type Setting = (&'static str, &'static [&'static str], String);
+
+fn tool_settings(config: &Config) -> Vec<Setting> {
+    let toolchain_name =
+        |compiler: &str, language| format!("{compiler} ({})", config.toolchain(language).name());
+
+    let mut settings: Vec<Setting> = Vec::new();
+
+    if let Some(sysroot) = &config.sysroot {
+        settings.push(("Sysroot", &[SYSROOT_KEY], sysroot.clone()));
+    }
+    settings.extend([
+        (
+            "Compiler",
+            &[COMPILER_KEY][..],
+            toolchain_name(&config.compiler, Language::Cxx),
+        ),
+        (
+            "C compiler",
+            &[C_COMPILER_KEY],
+            toolchain_name(&config.c_compiler, Language::C),
+        ),
+        ("Debugger", &[DEBUGGER_KEY], config.debugger.clone()),
+        (
+            "Debugger kind",
+            &[DEBUGGER_KIND_KEY, DEBUGGER_KEY],
+            config.debugger_kind.name().to_owned(),
+        ),
+        ("Linter", &[LINTER_KEY], config.linter.clone()),
+        ("Formatter", &[FORMATTER_KEY], config.formatter.clone()),
+    ]);
+
+    settings
+}
+
+fn flag_settings(config: &Config) -> Vec<Setting> {
+    let mut settings: Vec<Setting> = vec![
+        ("Flags", &[FLAGS_KEY], format!("{:?}", config.flags)),
+        ("C flags", &[C_FLAGS_KEY], format!("{:?}", config.c_flags)),
+        (
+            "C++ flags",
+            &[CXX_FLAGS_KEY],
+            format!("{:?}", config.cxx_flags),
+        ),
+        (
+            "Asm flags",
+            &[ASM_FLAGS_KEY],
+            format!("{:?}", config.asm_flags),
+        ),
+    ];
+    if let Some(c_standard) = &config.c_standard {
+        settings.push(("C standard", &[C_STANDARD_KEY, STD_KEY], c_standard.clone()));
+    }
+    if let Some(cxx_standard) = &config.cxx_standard {
+        settings.push((
+            "C++ standard",
+            &[CXX_STANDARD_KEY, STD_KEY],
+            cxx_standard.clone(),
+        ));
+    }
+    if let Some(warnings) = config.warnings {
+        settings.push((
+            "Warnings",
+            &[WARNINGS_KEY],
+            format!("{warnings:?} {:?}", warnings.flags()),
+        ));
+    }
+    settings.extend([
+        (
+            "Werror",
+            &[WARNINGS_AS_ERRORS_KEY][..],
+            config.warnings_as_errors.to_string(),
+        ),
+        ("Defines", &[DEFINES_KEY], format!("{:?}", config.defines)),
+        (
+            "Include dirs",
+            &[INCLUDE_DIRS_KEY],
+            format!("{:?}", config.include_dirs),
+        ),
+        (
+            "Debug flags",
+            &[DEBUG_FLAGS_KEY],
+            format!("{:?}", config.debug_flags),
+        ),
+        (
+            "Release flags",
+            &[RELEASE_FLAGS_KEY],
+            format!("{:?}", config.release_flags),
+        ),
+        (
+            "Linker flags",
+            &[LINKER_FLAGS_KEY],
+            format!("{:?}", config.linker_flags),
+        ),
+        ("LTO", &[LTO_KEY], config.lto.to_string()),
+        (
+            "Sanitizers",
+            &[SANITIZERS_KEY],
+            format!("{:?}", config.sanitizers),
+        ),
+    ]);
+    for flag_override in &config.flag_overrides {
+        settings.push((
+            "Flags override",
+            &[FLAGS_OVERRIDE_KEY],
+            format!(
+                "{} +{:?} -{:?}",
+                flag_override.path, flag_override.flags, flag_override.remove_flags
+            ),
+        ));
+    }
+
+    settings
 }
 
-fn show_config_command(config: &Config) {
+fn source_settings(config: &Config) -> Vec<Setting> {
+    vec![
+        (
+            "C extensions",
+            &[C_EXTENSIONS_KEY],
+            format!("{:?}", config.c_extensions),
+        ),
+        (
+            "C++ extensions",
+            &[CXX_EXTENSIONS_KEY],
+            format!("{:?}", config.cxx_extensions),
+        ),
+        (
+            "Asm extensions",
+            &[ASM_EXTENSIONS_KEY],
+            format!("{:?}", config.asm_extensions),
+        ),
+        (
+            "Header extensions",
+            &[HEADER_EXTENSIONS_KEY],
+            format!("{:?}", config.header_extensions),
+        ),
+        ("Exclude", &[EXCLUDE_KEY], format!("{:?}", config.exclude)),
+        (
+            "Respect gitignore",
+            &[RESPECT_GITIGNORE_KEY],
+            config.respect_gitignore.to_string(),
+        ),
+    ]
+}
+
+fn linter_settings(config: &Config) -> Vec<Setting> {
+    let mut settings: Vec<Setting> = vec![
+        (
+            "Linter checks",
+            &[LINTER_CHECKS_KEY],
+            format!("{:?}", config.linter_checks.checks),
+        ),
+        (
+            "Linter errors",
+            &[LINTER_WARNINGS_AS_ERRORS_KEY],
+            format!("{:?}", config.linter_checks.warnings_as_errors),
+        ),
+        (
+            "Linter options",
+            &[LINTER_CHECK_OPTIONS_KEY],
+            format!("{:?}", config.linter_checks.check_options),
+        ),
+    ];
+    for linter_override in &config.linter_overrides {
+        settings.push((
+            "Linter override",
+            &[LINTER_OVERRIDE_KEY],
+            format!(
+                "{} {:?} {:?} {:?}",
+                linter_override.path,
+                linter_override.config.checks,
+                linter_override.config.warnings_as_errors,
+                linter_override.config.check_options
+            ),
+        ));
+    }
+
+    settings
+}
+
+fn config_command(config: &Config, show_origin: bool) {
+    // Without target sections the compiler is not asked for its target while reading the
+    // configuration
+    let (target_triple, target_origin) = if config.cross_compiling {
+        (config.target.triple.clone(), "--target option")
+    } else if let Some(triple) = target::compiler_triple(&config.compiler) {
+        (triple, "compiler default")
+    } else {
+        (config.target.triple.clone(), "embargo host")
+    };
+
+    let settings = std::iter::once(("Target", target_origin, target_triple)).chain(
+        tool_settings(config)
+            .into_iter()
+            .chain(flag_settings(config))
+            .chain(source_settings(config))
+            .chain(linter_settings(config))
+            .map(|(label, keys, value)| (label, config.origin(keys), value)),
+    );
+
     println!("Embargo is configured as follow: ");
-    println!("    Compiler          {}", config.compiler);
-    println!("    Debugger          {}", config.debugger);
-    println!("    Linter            {}", config.linter);
-    println!("    Flags             {:?}", config.flags);
-    println!("    Debug flags       {:?}", config.debug_flags);
-    println!("    Release flags     {:?}", config.release_flags);
-    println!("    Linker flags      {:?}", config.linker_flags);
-    println!("    Linter checks     {}", config.linter_checks);
+    for (label, origin, value) in settings {
+        if show_origin {
+            println!("    {label:<18}{value}  [{origin}]");
+        } else {
+            println!("    {label:<18}{value}");
+        }
+    }
 }
 
-fn clangd_config_command(config: &Config) {
+fn clangd_config_command(config: &Config) -> Result<(), Error> {
     let mut compile_flags = String::new();
 
-    compile_flags.push_str("-Iinclude\n");
     compile_flags.push_str("-Isrc\n");
 
-    for flag in &config.flags {
+    let mut flags = common_flags(config);
+    flags.append(&mut toolchain_flags(config, Toolchain::clang(), false));
+
+    for flag in &flags {
         compile_flags.push_str(flag);
         compile_flags.push('\n');
     }
 
-    if let Err(error) = std::fs::write(COMPILE_FLAGS_FILE, compile_flags) {
-        eprintln!("Can't write {COMPILE_FLAGS_FILE} : {error}");
-    }
+    std::fs::write(COMPILE_FLAGS_FILE, compile_flags)
+        .map_err(|error| Error::Io(format!("Can't write {COMPILE_FLAGS_FILE} : {error}")))?;
+
+    // Unlike compile_flags.txt, the compilation database has the flags of each source file
+    let compile_commands = find_srcs(config)
+        .map_err(Error::Io)?
+        .into_iter()
+        .map(|source| {
+            let (compiler, flags) = source_compile_flags(config, false, &source);
+
+            CompileCommand {
+                compiler,
+                flags,
+                output: object_path(&build_subdir(config, false), &source),
+                file: source,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    compilation_database::write(&compile_commands).map_err(Error::Io)?;
+
+    clang_tidy::write_config_files(
+        &config.linter_checks,
+        &config.linter_overrides,
+        &[BUILD_DIR],
+    )
+    .map(|_| ())
+    .map_err(Error::Io)
 }
 
-fn clean_command() {
+fn clean_command() -> Result<(), Error> {
     if std::path::Path::new(BUILD_DIR).is_dir() {
         if let Err(error) = std::fs::remove_dir_all(BUILD_DIR) {
-            eprintln!("Can't remove {BUILD_DIR} directory : {error}");
+            return Err(Error::Io(format!(
+                "Can't remove {BUILD_DIR} directory : {error}"
+            )));
         }
     }
+
+    Ok(())
 }
 
 #[derive(Parser)]
@@ -608,10 +2341,78 @@ fn clean_command() {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Build for another target (aarch64-linux-gnu, ...), in its own build directory
+    #[arg(long, global = true, value_name = "TRIPLE")]
+    target: Option<String>,
+
+    /// When to color the output of embargo and of the tools it runs
+    #[arg(long, global = true, value_enum, default_value_t)]
+    color: ColorChoice,
+
+    /// Replace a setting of the configuration files for this run (--config 'compiler = "g++"'),
+    /// can be repeated
+    #[arg(long = "config", global = true, value_name = "KEY=VALUE")]
+    config_options: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WatchedCommand {
+    /// Check the code for errors, in place of a test command which embargo doesn't have yet
+    Check,
+    Build,
+    Run,
+    Lint,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum LintFormat {
+    /// Diagnostics as printed by the linter
+    #[default]
+    Text,
+
+    /// Array of diagnostic records
+    Json,
+
+    /// SARIF 2.1.0 document
+    Sarif,
+}
+
+#[derive(Args, Default)]
+#[allow(clippy::struct_excessive_bools)]
+struct LintArgs {
+    /// Apply the fixes suggested by the linter
+    #[arg(long)]
+    fix: bool,
+
+    /// Apply fixes even if the working tree has uncommitted changes
+    #[arg(long, requires = "fix")]
+    allow_dirty: bool,
+
+    /// Only lint the files changed since the last commit (or --base), and the sources including
+    /// them
+    #[arg(long)]
+    changed: bool,
+
+    /// Git revision the changes are computed from
+    #[arg(long, requires = "changed", value_name = "REVISION")]
+    base: Option<String>,
+
+    /// Record the current findings in lint-baseline.json, the next runs only report and fail on
+    /// new findings
+    #[arg(long, conflicts_with = "changed")]
+    update_baseline: bool,
+
+    /// Output format of the diagnostics, json and sarif documents are printed on stdout alone
+    #[arg(long, value_enum, default_value_t)]
+    format: LintFormat,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Check the code for errors without building the app
+    Check,
+
     /// Build the app with debug information
     Build,
 
@@ -628,46 +2429,191 @@ enum Commands {
     Debug,
 
     /// Run the linter on your project to find common mistakes
-    Lint,
+    Lint(LintArgs),
+
+    /// Format the code of your project
+    Fmt {
+        /// Only report files that are not formatted, without modifying them
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Run a command again each time a file of the project changes
+    Watch {
+        #[arg(value_enum, default_value = "build")]
+        command: WatchedCommand,
+    },
 
     /// Creates a default project in the current directory
     Init,
 
-    /// Show embargo configuration as defined after reading Embargo.toml
-    ShowConfig,
+    /// Show embargo configuration as defined after reading the configuration files
+    #[command(alias = "show-config")]
+    Config {
+        /// Show the file, section or environment variable each setting comes from
+        #[arg(long)]
+        show_origin: bool,
+    },
 
-    /// Generate the compile_flags.txt file for use with the clangd language server
+    /// Generate the `compile_flags.txt` file for use with the clangd language server
     ClangdConfig,
 
     /// Remove the build directory
     Clean,
 }
 
-fn main() {
+fn main() -> ExitCode {
     let arguments = Cli::parse();
 
-    match arguments.command {
+    color::init(arguments.color);
+
+    let result = match arguments.command {
         Commands::Init => init_command(),
-        _ => match read_configuration(".") {
-            Ok(config) => {
-                match arguments.command {
-                    // Commands for use inside a project
-                    Commands::Build => build_command(&config),
-                    Commands::ReleaseBuild => release_build_command(&config),
-                    Commands::Run => run_command(&config),
-                    Commands::ReleaseRun => release_run_command(&config),
-                    Commands::Debug => debug_command(&config),
-                    Commands::Lint => lint_command(&config),
-                    Commands::ShowConfig => show_config_command(&config),
-                    Commands::ClangdConfig => clangd_config_command(&config),
-                    Commands::Clean => clean_command(), // Doesn't need configuration, but for safety can only be used inside a project
-
-                    Commands::Init => init_command(), // Unreachable
+        _ => {
+            match read_configuration(".", arguments.target.as_deref(), &arguments.config_options) {
+                Ok(config) => {
+                    match arguments.command {
+                        // Commands for use inside a project
+                        Commands::Check => check_command(&config),
+                        Commands::Build => build_command(&config),
+                        Commands::ReleaseBuild => release_build_command(&config),
+                        Commands::Run => run_command(&config),
+                        Commands::ReleaseRun => release_run_command(&config),
+                        Commands::Debug => debug_command(&config),
+                        Commands::Lint(args) => lint_command(&config, &args),
+                        Commands::Config { show_origin } => {
+                            config_command(&config, show_origin);
+                            Ok(())
+                        }
+                        Commands::ClangdConfig => clangd_config_command(&config),
+                        Commands::Fmt { check } => fmt_command(&config, check),
+                        Commands::Watch { command } => {
+                            watch_command(config, command, &arguments.config_options)
+                        }
+                        Commands::Clean => clean_command(), // Doesn't need configuration, but for safety can only be used inside a project
+
+                        Commands::Init => init_command(), // Unreachable
+                    }
                 }
+                Err(err_msg) => Err(Error::Config(err_msg)),
             }
-            Err(err_msg) => {
-                eprintln!("{err_msg}");
-            }
-        },
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", color::error(&error.to_string()));
+            error.exit_code()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(variables: &[(&str, &str)]) -> Vec<(String, Value)> {
+        let variables = variables
+            .iter()
+            .map(|(variable, value)| ((*variable).to_owned(), (*value).to_owned()))
+            .collect();
+
+        environment_overrides(&variables)
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+
+    fn list(items: &[&str]) -> Value {
+        Value::Array(items.iter().map(|item| Value::from(*item)).collect())
+    }
+
+    #[test]
+    fn embargo_variable_names() {
+        assert_eq!(
+            environment_overrides(&HashMap::from([(
+                "EMBARGO_LINTER_CHECKS".to_owned(),
+                r#"["modernize-*"]"#.to_owned()
+            )])),
+            vec![(
+                "linter-checks".to_owned(),
+                list(&["modernize-*"]),
+                "EMBARGO_LINTER_CHECKS environment variable".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn toml_values() {
+        assert_eq!(environment_value("lto", "true"), Value::Boolean(true));
+        assert_eq!(
+            environment_value("release-flags", r#"["-O3", "-march=native"]"#),
+            list(&["-O3", "-march=native"])
+        );
+        assert_eq!(
+            environment_value("compiler", r#""clang++""#),
+            Value::from("clang++")
+        );
+    }
+
+    #[test]
+    fn string_fallback() {
+        assert_eq!(
+            environment_value("compiler", "clang++-17"),
+            Value::from("clang++-17")
+        );
+        assert_eq!(
+            environment_value("sysroot", "/usr/aarch64-linux-gnu"),
+            Value::from("/usr/aarch64-linux-gnu")
+        );
+    }
+
+    #[test]
+    fn whitespace_lists() {
+        assert_eq!(environment_value("c-flags", "-O1 -g"), list(&["-O1", "-g"]));
+        assert_eq!(
+            environment_value("c-flags", r#""-O1 -g""#),
+            list(&["-O1", "-g"])
+        );
+        assert_eq!(
+            environment_value("sanitizers", "address"),
+            list(&["address"])
+        );
+        // Valid toml, but not a list
+        assert_eq!(environment_value("flags", "-1"), list(&["-1"]));
+    }
+
+    #[test]
+    fn conventional_variables() {
+        assert_eq!(
+            overrides(&[("CXX", "g++"), ("CFLAGS", " -O1  -g "), ("LDFLAGS", "-lm")]),
+            vec![
+                (COMPILER_KEY.to_owned(), Value::from("g++")),
+                (C_FLAGS_KEY.to_owned(), list(&["-O1", "-g"])),
+                (LINKER_FLAGS_KEY.to_owned(), list(&["-lm"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn embargo_variables_come_last() {
+        let values = overrides(&[("EMBARGO_COMPILER", "clang++"), ("CXX", "g++")]);
+
+        assert_eq!(
+            values,
+            vec![
+                (COMPILER_KEY.to_owned(), Value::from("g++")),
+                (COMPILER_KEY.to_owned(), Value::from("clang++")),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_variables_are_ignored() {
+        assert_eq!(
+            overrides(&[("CXX", ""), ("CFLAGS", " "), ("EMBARGO_LINTER", "")]),
+            vec![]
+        );
     }
 }
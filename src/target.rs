@@ -0,0 +1,412 @@
+use std::process::Command;
+
+/// The platform the app is built for
+#[derive(Clone, Debug)]
+pub struct Target {
+    /// Target triple, as printed by `clang++ -dumpmachine` (`x86_64-pc-linux-gnu`,
+    /// `arm-none-eabi`, ...)
+    pub triple: String,
+    pub arch: String,
+    pub os: String,
+
+    /// `unix`, `windows` or empty for bare metal targets
+    pub family: String,
+    pub env: String,
+}
+
+impl Target {
+    /// Target the compiler produces code for by default, the platform embargo runs on is used
+    /// if the compiler can't tell
+    pub fn host(compiler: &str) -> Self {
+        compiler_triple(compiler).map_or_else(Self::native, |triple| Self::from_triple(&triple))
+    }
+
+    /// Platform embargo runs on, its triple is only `arch-os` as the compiler isn't asked
+    pub fn native() -> Self {
+        let os = std::env::consts::OS;
+
+        Self {
+            triple: format!("{}-{os}", std::env::consts::ARCH),
+            arch: std::env::consts::ARCH.to_owned(),
+            os: os.to_owned(),
+            family: std::env::consts::FAMILY.to_owned(),
+            env: String::new(),
+        }
+    }
+
+    /// Guesses the target properties from a triple, the vendor part is optional
+    pub fn from_triple(triple: &str) -> Self {
+        let components = triple.split('-').collect::<Vec<_>>();
+
+        let arch = components.first().copied().unwrap_or_default();
+
+        let os_index = components
+            .iter()
+            .skip(1)
+            .position(|component| os_name(component).is_some())
+            .map(|position| position + 1);
+
+        let (os, env) = os_index.map_or(("none", String::new()), |index| {
+            let os = os_name(components[index]).unwrap_or("none");
+
+            let env = if components[index] == "mingw32" {
+                "gnu".to_owned()
+            } else {
+                components[index + 1..].join("-")
+            };
+
+            // aarch64-linux-android
+            if env == "android" {
+                ("android", env)
+            } else {
+                (os, env)
+            }
+        });
+
+        let family = match os {
+            "windows" => "windows",
+            "none" => "",
+            _ => "unix",
+        };
+
+        Self {
+            triple: triple.to_owned(),
+            arch: arch.to_owned(),
+            os: os.to_owned(),
+            family: family.to_owned(),
+            env,
+        }
+    }
+
+    pub fn exe_extension(&self) -> &'static str {
+        if self.os == "windows" {
+            ".exe"
+        } else {
+            ""
+        }
+    }
+
+    /// Returns true if a `[target.<name>]` section applies to this target, `name` is either
+    /// a triple or a `cfg(...)` expression
+    pub fn matches(&self, name: &str) -> Result<bool, String> {
+        if name.starts_with("cfg(") {
+            let mut parser = CfgParser {
+                tokens: tokenize(name)?,
+                position: 0,
+            };
+
+            parser.expect(&Token::Ident("cfg".to_owned()))?;
+            parser.expect(&Token::Open)?;
+            let predicate = parser.predicate()?;
+            parser.expect(&Token::Close)?;
+
+            if parser.position != parser.tokens.len() {
+                return Err(format!("Unexpected text after the end of {name}"));
+            }
+
+            self.eval(&predicate)
+        } else {
+            Ok(name == self.triple)
+        }
+    }
+
+    fn eval(&self, predicate: &Predicate) -> Result<bool, String> {
+        match predicate {
+            Predicate::Name(name) => match name.as_str() {
+                "unix" | "windows" => Ok(self.family == *name),
+                _ => Err(format!("Unknown cfg name {name}, expected unix or windows")),
+            },
+            Predicate::KeyValue(key, value) => match key.as_str() {
+                "arch" => Ok(self.arch == *value),
+                "os" => Ok(self.os == *value),
+                "family" => Ok(self.family == *value),
+                "env" => Ok(self.env == *value),
+                _ => Err(format!(
+                    "Unknown cfg key {key}, expected arch, os, family or env"
+                )),
+            },
+            Predicate::All(predicates) => {
+                for predicate in predicates {
+                    if !self.eval(predicate)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Predicate::Any(predicates) => {
+                for predicate in predicates {
+                    if self.eval(predicate)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Predicate::Not(predicate) => Ok(!self.eval(predicate)?),
+        }
+    }
+}
+
+/// Triple the compiler produces code for by default, as printed by `-dumpmachine`
+pub fn compiler_triple(compiler: &str) -> Option<String> {
+    Command::new(compiler)
+        .arg("-dumpmachine")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .filter(|triple| !triple.is_empty())
+}
+
+/// Operating system named by a triple component
+fn os_name(component: &str) -> Option<&'static str> {
+    if component == "linux" {
+        Some("linux")
+    } else if component == "windows" || component == "mingw32" {
+        Some("windows")
+    } else if component.starts_with("darwin") || component.starts_with("macos") {
+        Some("macos")
+    } else if component.starts_with("ios") {
+        Some("ios")
+    } else if component.starts_with("freebsd") {
+        Some("freebsd")
+    } else if component == "none" || component == "elf" {
+        Some("none")
+    } else {
+        None
+    }
+}
+
+enum Predicate {
+    /// `unix`
+    Name(String),
+
+    /// `os = "linux"`
+    KeyValue(String, String),
+    All(Vec<Self>),
+    Any(Vec<Self>),
+    Not(Box<Self>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Open,
+    Close,
+    Comma,
+    Equal,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '=' => tokens.push(Token::Equal),
+            '"' => {
+                let mut string = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err(format!("Unterminated string in {text}")),
+                    }
+                }
+
+                tokens.push(Token::Str(string));
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return Err(format!("Unexpected character {c} in {text}")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct CfgParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl CfgParser {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(format!("Invalid cfg expression, expected {expected:?}")),
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, String> {
+        let Some(Token::Ident(ident)) = self.next() else {
+            return Err("Invalid cfg expression, expected a name".to_owned());
+        };
+        let ident = ident.clone();
+
+        match self.tokens.get(self.position) {
+            Some(Token::Equal) => {
+                self.position += 1;
+
+                match self.next() {
+                    Some(Token::Str(value)) => Ok(Predicate::KeyValue(ident, value.clone())),
+                    _ => Err(format!(
+                        "Invalid cfg expression, expected a string after {ident} ="
+                    )),
+                }
+            }
+            Some(Token::Open) => {
+                self.position += 1;
+
+                let mut predicates = Vec::new();
+
+                while self.tokens.get(self.position) != Some(&Token::Close) {
+                    predicates.push(self.predicate()?);
+
+                    // Items are separated by commas, a trailing comma is allowed
+                    match self.tokens.get(self.position) {
+                        Some(Token::Comma) => self.position += 1,
+                        Some(Token::Close) => {}
+                        _ => {
+                            return Err(format!(
+                                "Invalid cfg expression, expected , or ) in {ident}(...)"
+                            ))
+                        }
+                    }
+                }
+
+                self.position += 1;
+
+                match ident.as_str() {
+                    "all" => Ok(Predicate::All(predicates)),
+                    "any" => Ok(Predicate::Any(predicates)),
+                    "not" if predicates.len() == 1 => {
+                        Ok(Predicate::Not(Box::new(predicates.remove(0))))
+                    }
+                    "not" => Err("not() takes exactly one cfg expression".to_owned()),
+                    _ => Err(format!(
+                        "Unknown cfg operator {ident}, expected all, any or not"
+                    )),
+                }
+            }
+            _ => Ok(Predicate::Name(ident)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_target(target: &Target, arch: &str, os: &str, family: &str, env: &str) {
+        assert_eq!(
+            (
+                target.arch.as_str(),
+                target.os.as_str(),
+                target.family.as_str(),
+                target.env.as_str()
+            ),
+            (arch, os, family, env)
+        );
+    }
+
+    #[test]
+    fn bare_metal_triple() {
+        let target = Target::from_triple("arm-none-eabi");
+
+        assert_target(&target, "arm", "none", "", "eabi");
+        assert_eq!(target.exe_extension(), "");
+    }
+
+    #[test]
+    fn mingw_triple() {
+        let target = Target::from_triple("x86_64-w64-mingw32");
+
+        assert_target(&target, "x86_64", "windows", "windows", "gnu");
+        assert_eq!(target.exe_extension(), ".exe");
+    }
+
+    #[test]
+    fn android_triple() {
+        let target = Target::from_triple("aarch64-linux-android");
+
+        assert_target(&target, "aarch64", "android", "unix", "android");
+    }
+
+    #[test]
+    fn linux_triple_with_vendor() {
+        let target = Target::from_triple("x86_64-pc-linux-gnu");
+
+        assert_target(&target, "x86_64", "linux", "unix", "gnu");
+    }
+
+    #[test]
+    fn triple_section() {
+        let target = Target::from_triple("arm-none-eabi");
+
+        assert_eq!(target.matches("arm-none-eabi"), Ok(true));
+        assert_eq!(target.matches("aarch64-linux-gnu"), Ok(false));
+    }
+
+    #[test]
+    fn cfg_evaluation() {
+        let linux = Target::from_triple("x86_64-linux-gnu");
+        let windows = Target::from_triple("x86_64-w64-mingw32");
+
+        assert_eq!(linux.matches("cfg(unix)"), Ok(true));
+        assert_eq!(windows.matches("cfg(unix)"), Ok(false));
+        assert_eq!(linux.matches(r#"cfg(os = "linux")"#), Ok(true));
+        assert_eq!(
+            linux.matches(r#"cfg(all(unix, arch = "x86_64", env = "gnu"))"#),
+            Ok(true)
+        );
+        assert_eq!(
+            linux.matches(r#"cfg(any(windows, os = "macos"))"#),
+            Ok(false)
+        );
+        assert_eq!(windows.matches(r#"cfg(not(family = "unix"))"#), Ok(true));
+        assert_eq!(linux.matches("cfg(all(unix, not(windows),))"), Ok(true));
+    }
+
+    #[test]
+    fn cfg_errors() {
+        let target = Target::from_triple("x86_64-linux-gnu");
+
+        for name in [
+            "cfg(all(unix windows))",
+            "cfg(linux)",
+            r#"cfg(vendor = "pc")"#,
+            "cfg(not(unix, windows))",
+            "cfg(one(unix))",
+            "cfg(unix",
+            "cfg(unix) extra",
+            r#"cfg(os = "linux)"#,
+            "cfg(os = linux)",
+        ] {
+            assert!(target.matches(name).is_err(), "{name} should be invalid");
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    Clang,
+    Gcc,
+
+    /// Compiler embargo doesn't recognize, it is given clang flags but no colored diagnostics
+    Unknown,
+}
+
+/// Compiler family and version, generic options are translated to the flags it understands
+#[derive(Clone, Copy, Debug)]
+pub struct Toolchain {
+    pub family: Family,
+
+    /// Major and minor version
+    pub version: Option<(u32, u32)>,
+}
+
+impl Toolchain {
+    /// Identifies the compiler from its `--version` output
+    pub fn detect(compiler: &str) -> Self {
+        let output = Command::new(compiler)
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+
+        Self::from_version_output(&output)
+    }
+
+    /// clang-tidy and clangd use the clang frontend whatever the compiler is
+    pub const fn clang() -> Self {
+        Self {
+            family: Family::Clang,
+            version: None,
+        }
+    }
+
+    fn from_version_output(output: &str) -> Self {
+        let first_line = output.lines().next().unwrap_or_default();
+
+        // "clang version 17.0.6", "Apple clang version 15.0.0 (clang-1500.1.0.2.5)"
+        if let Some((_, version)) = first_line.split_once("clang version ") {
+            return Self {
+                family: Family::Clang,
+                version: parse_version(version),
+            };
+        }
+
+        // "g++ (Ubuntu 11.4.0-1ubuntu1~22.04) 11.4.0", "gcc (GCC) 13.2.1 20230801",
+        // "arm-none-eabi-gcc (GNU Arm Embedded Toolchain 10.3-2021.10) 10.3.1 20210824 (release)"
+        if output.contains("Free Software Foundation") {
+            return Self {
+                family: Family::Gcc,
+                version: first_line
+                    .split_once(") ")
+                    .and_then(|(_, version)| version.split_whitespace().next())
+                    .and_then(parse_version),
+            };
+        }
+
+        Self {
+            family: Family::Unknown,
+            version: None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        let family = match self.family {
+            Family::Clang => "clang",
+            Family::Gcc => "gcc",
+            Family::Unknown => "unknown",
+        };
+
+        match self.version {
+            Some((major, minor)) => format!("{family} {major}.{minor}"),
+            None => family.to_owned(),
+        }
+    }
+
+    /// Flag enabling or disabling colored diagnostics, as the compiler output is captured its
+    /// own terminal detection can't be used
+    pub fn color_flag(&self, enabled: bool) -> Option<&'static str> {
+        match self.family {
+            Family::Clang if enabled => Some("-fcolor-diagnostics"),
+            Family::Clang => Some("-fno-color-diagnostics"),
+            // Added in gcc 4.9
+            Family::Gcc if self.version.is_none_or(|version| version >= (4, 9)) => {
+                if enabled {
+                    Some("-fdiagnostics-color=always")
+                } else {
+                    Some("-fdiagnostics-color=never")
+                }
+            }
+            Family::Gcc | Family::Unknown => None,
+        }
+    }
+
+    /// gcc compilers are built for a single target, their name is prefixed by the triple instead
+    pub fn target_flag(&self, triple: &str) -> Option<String> {
+        match self.family {
+            Family::Clang | Family::Unknown => Some(format!("--target={triple}")),
+            Family::Gcc => None,
+        }
+    }
+
+    pub fn lto_flag(&self) -> &'static str {
+        match self.family {
+            // Runs the link time optimization in parallel, added in gcc 10
+            Family::Gcc if self.version.is_some_and(|version| version >= (10, 0)) => "-flto=auto",
+            Family::Clang | Family::Gcc | Family::Unknown => "-flto",
+        }
+    }
+}
+
+/// Parses the `major.minor` part of a version like `11.4.0-1ubuntu1`
+fn parse_version(text: &str) -> Option<(u32, u32)> {
+    let mut numbers = text.split(|c: char| !c.is_ascii_digit());
+
+    let major = numbers.next()?.parse().ok()?;
+    let minor = numbers
+        .next()
+        .and_then(|minor| minor.parse().ok())
+        .unwrap_or(0);
+
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GCC_COPYRIGHT: &str = "Copyright (C) 2021 Free Software Foundation, Inc.";
+
+    fn detect(first_line: &str, rest: &str) -> Toolchain {
+        Toolchain::from_version_output(&format!("{first_line}\n{rest}\n"))
+    }
+
+    fn gcc(major: u32, minor: u32) -> Toolchain {
+        Toolchain {
+            family: Family::Gcc,
+            version: Some((major, minor)),
+        }
+    }
+
+    #[test]
+    fn ubuntu_gcc() {
+        let toolchain = detect("g++ (Ubuntu 11.4.0-1ubuntu1~22.04) 11.4.0", GCC_COPYRIGHT);
+
+        assert_eq!(toolchain.family, Family::Gcc);
+        assert_eq!(toolchain.version, Some((11, 4)));
+        assert_eq!(toolchain.name(), "gcc 11.4");
+    }
+
+    #[test]
+    fn arm_gcc() {
+        let toolchain = detect(
+            "arm-none-eabi-gcc (GNU Arm Embedded Toolchain 10.3-2021.10) 10.3.1 20210824 (release)",
+            GCC_COPYRIGHT,
+        );
+
+        assert_eq!(toolchain.family, Family::Gcc);
+        assert_eq!(toolchain.version, Some((10, 3)));
+    }
+
+    #[test]
+    fn gcc_with_date() {
+        let toolchain = detect("gcc (GCC) 13.2.1 20230801", GCC_COPYRIGHT);
+
+        assert_eq!(toolchain.version, Some((13, 2)));
+    }
+
+    #[test]
+    fn apple_clang() {
+        let toolchain = detect(
+            "Apple clang version 15.0.0 (clang-1500.1.0.2.5)",
+            "Target: arm64-apple-darwin23.2.0",
+        );
+
+        assert_eq!(toolchain.family, Family::Clang);
+        assert_eq!(toolchain.version, Some((15, 0)));
+    }
+
+    #[test]
+    fn vendor_clang() {
+        let toolchain = detect(
+            "Ubuntu clang version 14.0.0-1ubuntu1.1",
+            "Target: x86_64-pc-linux-gnu",
+        );
+
+        assert_eq!(toolchain.family, Family::Clang);
+        assert_eq!(toolchain.version, Some((14, 0)));
+        assert_eq!(toolchain.name(), "clang 14.0");
+    }
+
+    #[test]
+    fn unknown_compiler() {
+        let toolchain = detect("tcc version 0.9.27 (x86_64 Linux)", "");
+
+        assert_eq!(toolchain.family, Family::Unknown);
+        assert_eq!(toolchain.version, None);
+        assert_eq!(toolchain.name(), "unknown");
+        assert_eq!(Toolchain::from_version_output("").family, Family::Unknown);
+    }
+
+    #[test]
+    fn gcc_color_flag() {
+        assert_eq!(gcc(4, 8).color_flag(true), None);
+        assert_eq!(
+            gcc(4, 9).color_flag(true),
+            Some("-fdiagnostics-color=always")
+        );
+        assert_eq!(
+            gcc(4, 9).color_flag(false),
+            Some("-fdiagnostics-color=never")
+        );
+        assert_eq!(
+            Toolchain {
+                family: Family::Gcc,
+                version: None
+            }
+            .color_flag(true),
+            Some("-fdiagnostics-color=always")
+        );
+        assert_eq!(
+            Toolchain::clang().color_flag(true),
+            Some("-fcolor-diagnostics")
+        );
+    }
+
+    #[test]
+    fn gcc_lto_flag() {
+        assert_eq!(gcc(9, 5).lto_flag(), "-flto");
+        assert_eq!(gcc(10, 0).lto_flag(), "-flto=auto");
+        assert_eq!(Toolchain::clang().lto_flag(), "-flto");
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Changes arriving within this delay of each other are merged in a single rebuild, editors
+/// often write a file in several steps
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+/// Watches files and directories of the project (inotify on Linux)
+pub struct ChangeWatcher {
+    // Watching stops when the watcher is dropped
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    root: PathBuf,
+    watched: Vec<PathBuf>,
+
+    /// Files outside of the project, by absolute path
+    external: Vec<PathBuf>,
+}
+
+impl ChangeWatcher {
+    /// Watches the given files and directories (recursively), paths are relative to the
+    /// current directory which must be the project root
+    pub fn new(paths: &[&str]) -> Result<Self, String> {
+        let root = std::fs::canonicalize(".")
+            .map_err(|error| format!("Can't find the project directory : {error}"))?;
+
+        let (sender, events) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|error| format!("Can't watch for file changes : {error}"))?;
+
+        // Watching the root itself catches watched files being replaced by editors and
+        // watched directories being created
+        watcher
+            .watch(&root, RecursiveMode::NonRecursive)
+            .map_err(|error| format!("Can't watch {} : {error}", root.display()))?;
+
+        for path in paths {
+            let full_path = root.join(path);
+
+            if full_path.is_dir() {
+                watcher
+                    .watch(&full_path, RecursiveMode::Recursive)
+                    .map_err(|error| format!("Can't watch {path} : {error}"))?;
+            }
+        }
+
+        Ok(Self {
+            watcher,
+            events,
+            root,
+            watched: paths.iter().map(PathBuf::from).collect(),
+            external: Vec::new(),
+        })
+    }
+
+    /// Watches a file outside of the project, through its directory so that the file being
+    /// created or replaced is seen. Nothing is watched if the directory doesn't exist
+    pub fn watch_file(&mut self, path: &Path) -> Result<(), String> {
+        let Some(dir) = path.parent().filter(|dir| dir.is_dir()) else {
+            return Ok(());
+        };
+
+        self.watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|error| format!("Can't watch {} : {error}", dir.display()))?;
+
+        self.external.push(path.to_owned());
+
+        Ok(())
+    }
+
+    /// Blocks until a watched file changes
+    pub fn wait_for_change(&mut self) -> Result<(), String> {
+        loop {
+            match self.events.recv() {
+                Ok(Ok(event)) => {
+                    self.watch_created_dirs(&event)?;
+
+                    if self.is_relevant(&event) {
+                        break;
+                    }
+                }
+                Ok(Err(error)) => return Err(format!("Can't watch for file changes : {error}")),
+                Err(_) => return Err("File watcher stopped unexpectedly".to_owned()),
+            }
+        }
+
+        loop {
+            match self.events.recv_timeout(DEBOUNCE_DELAY) {
+                Ok(Ok(event)) => self.watch_created_dirs(&event)?,
+                Ok(Err(_)) => {}
+                Err(RecvTimeoutError::Timeout) => return Ok(()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err("File watcher stopped unexpectedly".to_owned())
+                }
+            }
+        }
+    }
+
+    /// Watches the directories given to `new` that are created (or replaced by a branch switch)
+    /// after the watcher started, the root only reports changes of its direct entries
+    fn watch_created_dirs(&mut self, event: &Event) -> Result<(), String> {
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return Ok(());
+        }
+
+        for path in &event.paths {
+            let is_watched_dir = path.is_dir()
+                && path
+                    .strip_prefix(&self.root)
+                    .is_ok_and(|relative| self.watched.iter().any(|watched| relative == watched));
+
+            if is_watched_dir {
+                self.watcher
+                    .watch(path, RecursiveMode::Recursive)
+                    .map_err(|error| format!("Can't watch {} : {error}", path.display()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_relevant(&self, event: &Event) -> bool {
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return false;
+        }
+
+        event.paths.iter().any(|path| self.is_watched(path))
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.external.iter().any(|external| path == external)
+            || path.strip_prefix(&self.root).is_ok_and(|relative| {
+                self.watched
+                    .iter()
+                    .any(|watched| relative.starts_with(watched))
+            })
+    }
+}